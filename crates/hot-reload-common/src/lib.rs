@@ -1,6 +1,10 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
+mod script_error;
+
+pub use script_error::{parse_script_errors, strip_colors, OriginalLocation, ScriptError, ScriptErrorParser, ScriptLanguage, StackFrame};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitialData {
     pub resources_path: String,
@@ -23,6 +27,100 @@ pub struct ResourceChange {
     pub resource_name: String,
     pub change_type: ChangeType,
     pub file_path: String,
+    #[serde(default)]
+    pub reason: ReloadReason,
+    /// Commands FXServer runs for this change, `Auto` is resolved by the watcher
+    #[serde(default)]
+    pub strategy: ReloadStrategy,
+}
+
+/// How a resource is reloaded once its files changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReloadStrategy {
+    /// `ensure`, with a `refresh` first when files were added or removed
+    #[default]
+    Auto,
+    Ensure,
+    Restart,
+    StopStart,
+    RefreshEnsure,
+    /// Never reloaded automatically (database layers, core frameworks)
+    Never,
+    /// Console commands run in order, `{resource}` is replaced by the resource name
+    Custom { commands: Vec<String> },
+}
+
+impl ReloadStrategy {
+    /// The strategy actually applied to a change, `Auto` depends on what changed.
+    pub fn resolve(&self, change_type: &ChangeType) -> ReloadStrategy {
+        match (self, change_type) {
+            (ReloadStrategy::Auto, ChangeType::FileModified) => ReloadStrategy::Ensure,
+            (ReloadStrategy::Auto, _) => ReloadStrategy::RefreshEnsure,
+            (strategy, _) => strategy.clone(),
+        }
+    }
+}
+
+/// Why the watcher reloads a resource.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReloadReason {
+    /// One of its own files changed
+    #[default]
+    FileChanged,
+    /// A resource it declares as a dependency was reloaded
+    Dependency { resource: String },
+    /// Its manifest loads `@resource/file`, which changed
+    Import { resource: String, file: String },
+    /// Asked from the resource tree of the UI
+    Manual { action: ResourceAction },
+}
+
+/// Action run on resources from the UI, through the same path as reloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceAction {
+    Start,
+    Stop,
+    Restart,
+    RefreshEnsure,
+    /// What a file change would run, from the reload strategy of the resource
+    Reload,
+    /// Restores the files of a snapshot and reloads the resource
+    Rollback,
+}
+
+impl ResourceAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResourceAction::Start => "start",
+            ResourceAction::Stop => "stop",
+            ResourceAction::Restart => "restart",
+            ResourceAction::RefreshEnsure => "refresh_ensure",
+            ResourceAction::Reload => "reload",
+            ResourceAction::Rollback => "rollback",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Progress of a manual action on one resource, sent to every client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionStatus {
+    pub resource: String,
+    pub action: ResourceAction,
+    pub state: ActionState,
+    /// Answer of FXServer, or why the action failed
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,3 +129,433 @@ pub enum ChangeType {
     FileAdded,
     FileRemoved,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    pub diagnostics: Vec<ScanDiagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScanDiagnostic {
+    DuplicateResource { name: String, paths: Vec<String> },
+    NestedResource { name: String, path: String, parent: String },
+    NoScripts { name: String, path: String },
+    UnreadableDirectory { path: String, error: String },
+    DependencyCycle { resources: Vec<String> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatcherStats {
+    pub indexed_files: u64,
+    pub processed_events: u64,
+    pub suppressed_events: u64,
+    /// Events dropped because the pipeline queue was full, recovered by a resync
+    pub dropped_events: u64,
+}
+
+/// Files that appeared in or vanished from a resource since the initial data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeDelta {
+    pub resource_name: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Sent after the watcher had to rescan because file events were dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResyncReport {
+    pub reason: String,
+    pub resources: Vec<String>,
+    pub changed_files: usize,
+}
+
+/// A resource of the dependency graph and its outgoing edges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub name: String,
+    /// Resources of the tree declared with `dependency` / `dependencies`
+    pub dependencies: Vec<String>,
+    /// Resources of the tree it loads `@resource/file` scripts from
+    pub imports: Vec<String>,
+    /// Dependencies and imported resources that are not part of the scanned tree
+    pub missing: Vec<String>,
+    /// Listed in `no_cascade`, never restarted because a dependency reloaded
+    pub no_cascade: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceGraph {
+    pub nodes: Vec<GraphNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildStarted {
+    pub resource: String,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line printed by a build hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildOutput {
+    pub resource: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildFinished {
+    pub resource: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Exit status, timeout or spawn error when the build failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    /// `testsuite` of a JUnit report or the parent subtest in TAP
+    #[serde(default)]
+    pub suite: Option<String>,
+    pub status: TestStatus,
+    /// Failure or skip message, with the details printed by the runner
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Result of the tests run after a resource was reloaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestReport {
+    pub resource: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub cases: Vec<TestCase>,
+    /// Exit status, timeout or unreadable report, set even when some cases were parsed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A problem reported at a position of a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based, 0 when the tool gives no position
+    pub line: u32,
+    pub column: u32,
+    /// Rule or warning code, e.g. `W211` or `no-unused-vars`
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Everything one linter found in one file, replaces what it reported
+/// before. Empty once the file is clean.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintReport {
+    pub resource: String,
+    /// Relative to the resource root, with `/` separators
+    pub file: String,
+    /// Linter that produced the diagnostics, e.g. `luacheck`
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// One line printed in the FXServer console, `^0`-`^9` color codes included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleLine {
+    /// e.g. `script:myres`, `citizen-server-impl` or `resources`
+    pub channel: String,
+    pub message: String,
+    pub timestamp_ms: u64,
+}
+
+/// State of a resource on the server, as `GetResourceState` reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceState {
+    Started,
+    Starting,
+    Stopped,
+    Stopping,
+    /// Known to the server but never started since the last `refresh`
+    Uninitialized,
+    Missing,
+    #[serde(other)]
+    Unknown,
+}
+
+impl ResourceState {
+    /// Whether the server runs the resource or is about to
+    pub fn is_loaded(self) -> bool {
+        matches!(self, ResourceState::Started | ResourceState::Starting)
+    }
+}
+
+/// Every resource the server knows about, replaces the previous table.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceStates {
+    pub states: HashMap<String, ResourceState>,
+}
+
+/// One resource changed state on the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceStateChange {
+    pub resource: String,
+    pub state: ResourceState,
+}
+
+/// Strategies configured for resources, the others use `Auto`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReloadStrategies {
+    pub strategies: HashMap<String, ReloadStrategy>,
+    /// Why a change was not saved, only sent back to the client that made it
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// One step taken by the watcher's reload pipeline, kept for debugging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineLogEntry {
+    pub timestamp_ms: u64,
+    pub stage: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineLog {
+    pub entries: Vec<PipelineLogEntry>,
+}
+
+/// What a client may do, localhost and the main API key are operators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Sees everything, changes nothing on the server
+    #[default]
+    Viewer,
+    /// Also runs console commands
+    Operator,
+}
+
+/// Sent once a client is connected, tells it what it is allowed to do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub role: Role,
+    /// How the history names this client: the name it gave in `Hello` followed by
+    /// `(local)` or the fingerprint of its api key, `local` or the fingerprint alone without one
+    #[serde(default)]
+    pub user: String,
+}
+
+/// Answer to `ClientMessage::ExecuteCommand`, only sent to the client that asked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandResult {
+    /// Chosen by the client to match the answer with its request
+    pub id: u64,
+    pub command: String,
+    /// Console lines printed while the command ran
+    pub output: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Whether automatic reloads are held back, sent at connect and whenever it changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PauseState {
+    pub paused: bool,
+    pub reason: Option<String>,
+    pub since_ms: Option<u64>,
+    /// Reloads resume on their own at that time, the held changes are applied
+    pub until_ms: Option<u64>,
+    /// Changes held back while paused, one per resource
+    pub pending: Vec<ResourceChange>,
+}
+
+/// Whether file changes are reloaded right away or wait for an apply from a client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyMode {
+    #[default]
+    Auto,
+    Manual,
+}
+
+/// What changed in a resource since the last apply in manual mode, and what applying it does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub resource: String,
+    pub change_type: ChangeType,
+    /// Relative to the resource, every file changed since the last apply
+    pub files: Vec<String>,
+    /// What FXServer runs for the resource, `Never` when nothing would be sent
+    pub strategy: ReloadStrategy,
+    /// Importers and dependents reloaded along with it, in order
+    pub restarts: Vec<String>,
+}
+
+/// Sent at connect and whenever the mode or the collected changes change.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingChanges {
+    pub mode: ApplyMode,
+    pub changes: Vec<PendingChange>,
+}
+
+/// Failed automatic reloads of a resource, auto-reload stops for it once `tripped`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerState {
+    pub resource: String,
+    pub tripped: bool,
+    /// Failures still within the window of the breaker
+    pub failures: usize,
+    pub last_error: Option<String>,
+    pub tripped_at_ms: Option<u64>,
+}
+
+/// Files of a resource saved after a reload that went through, a rollback restores them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: u64,
+    pub created_ms: u64,
+    pub files: usize,
+    pub bytes: u64,
+    /// The files as they were right before a rollback, never picked as the latest
+    #[serde(default)]
+    pub before_rollback: bool,
+}
+
+/// Sent at connect for every resource and whenever a snapshot of one is taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotList {
+    pub resource: String,
+    /// Newest first
+    pub snapshots: Vec<SnapshotInfo>,
+}
+
+/// What a history entry records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// Files of the resource changed on disk, relative to the resource
+    Change { change_type: ChangeType, files: Vec<String> },
+    /// FXServer was asked to reload the resource
+    Reload {
+        reason: ReloadReason,
+        strategy: ReloadStrategy,
+        success: bool,
+        /// Answer of FXServer, or why there was none
+        message: Option<String>,
+    },
+    /// Console command run through the watcher
+    Command { command: String, error: Option<String> },
+    /// Request of a client that changes something, `refused` when its role was too low
+    Request { request: ClientMessage, refused: bool },
+}
+
+/// One line of the history file kept by the watcher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp_ms: u64,
+    /// Client behind the entry, `None` for what the watcher did on its own
+    pub user: Option<String>,
+    pub resources: Vec<String>,
+    pub event: HistoryEvent,
+}
+
+/// Answer to `ClientMessage::QueryHistory`, only sent to the client that asked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    /// Newest first
+    pub entries: Vec<HistoryEntry>,
+    /// More entries matched than the limit allowed
+    pub truncated: bool,
+}
+
+/// Requests sent by the UI to the watcher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Sent right after connecting (and authenticating), names the person behind the client in the history
+    Hello { user: String },
+    GetPipelineLog,
+    /// `Auto` removes the resource from the configured strategies
+    SetReloadStrategy { resource: String, strategy: ReloadStrategy },
+    /// Runs a command in the FXServer console, operators only
+    ExecuteCommand { id: u64, command: String },
+    /// Runs an action on each resource in dependency order, operators only
+    ResourceAction { resources: Vec<String>, action: ResourceAction },
+    /// Holds automatic reloads back until `Resume` or the duration ran out, operators only
+    Pause { reason: Option<String>, duration_ms: Option<u64> },
+    /// Reloads what changed while paused when `apply`, drops it otherwise, operators only
+    Resume { apply: bool },
+    /// Switches between reloading right away and collecting changes, operators only
+    SetApplyMode { mode: ApplyMode },
+    /// Reloads the changes collected for these resources in manual mode, operators only
+    ApplyPending { resources: Vec<String> },
+    /// Lets a resource whose breaker tripped reload again, operators only
+    ResetBreaker { resource: String },
+    /// Restores a snapshot, the latest when `snapshot` is `None`, and reloads the resource, operators only
+    Rollback { resource: String, snapshot: Option<u64> },
+    /// Reads the history, every filter is optional, newest entries first
+    QueryHistory {
+        from_ms: Option<u64>,
+        to_ms: Option<u64>,
+        resource: Option<String>,
+        user: Option<String>,
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatcherMessage {
+    ScanReport(ScanReport),
+    Stats(WatcherStats),
+    TreeDelta(TreeDelta),
+    Resync(ResyncReport),
+    PipelineLog(PipelineLog),
+    /// Sent for every resource the watcher asks FXServer to reload
+    Reload(ResourceChange),
+    Graph(ResourceGraph),
+    ReloadStrategies(ReloadStrategies),
+    BuildStarted(BuildStarted),
+    BuildOutput(BuildOutput),
+    BuildFinished(BuildFinished),
+    TestReport(TestReport),
+    Lint(LintReport),
+    ScriptError(ScriptError),
+    Console(ConsoleLine),
+    Session(Session),
+    CommandResult(CommandResult),
+    ActionStatus(ActionStatus),
+    ResourceStates(ResourceStates),
+    ResourceStateChange(ResourceStateChange),
+    Pause(PauseState),
+    PendingChanges(PendingChanges),
+    Breaker(BreakerState),
+    Snapshots(SnapshotList),
+    History(HistoryPage),
+}
@@ -0,0 +1,442 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptLanguage {
+    Lua,
+    JavaScript,
+    CSharp,
+}
+
+/// Where a position of generated JavaScript comes from, found through its source map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OriginalLocation {
+    /// Relative to the resource root, e.g. `src/server/main.ts`
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub function: Option<String>,
+    /// Set when the path is written `@resource/file`
+    pub resource: Option<String>,
+    /// Relative to the resource when `resource` is set, as printed otherwise
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    #[serde(default)]
+    pub original: Option<OriginalLocation>,
+}
+
+/// An error printed by FXServer for a Lua, JS or C# script.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptError {
+    pub language: ScriptLanguage,
+    /// From the `@resource/` path or the `[script:resource]` channel
+    pub resource: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub column: Option<u32>,
+    pub message: String,
+    pub frames: Vec<StackFrame>,
+    /// Set by the watcher when `file` has a source map next to it
+    #[serde(default)]
+    pub original: Option<OriginalLocation>,
+}
+
+/// Removes the `^0`-`^9` color codes of FXServer and ANSI escapes.
+pub fn strip_colors(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '^' if chars.peek().is_some_and(|next| next.is_ascii_digit()) => {
+                chars.next();
+            }
+            '\u{1b}' if chars.peek() == Some(&'[') => {
+                for next in chars.by_ref() {
+                    if next.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Splits `[    script:myres] text` into the channel and the text.
+fn split_channel(line: &str) -> (Option<&str>, &str) {
+    let trimmed = line.trim_start();
+    let Some(inner) = trimmed.strip_prefix('[') else {
+        return (None, line);
+    };
+    // `[C]: in function` is a Lua frame, channels are always followed by a space
+    match inner.split_once("] ") {
+        Some((channel, text)) if !channel.contains(['[', ']']) => (Some(channel.trim()), text),
+        _ => (None, line),
+    }
+}
+
+fn channel_resource(channel: Option<&str>) -> Option<String> {
+    channel?.strip_prefix("script:").map(|resource| resource.trim().to_string())
+}
+
+struct Location {
+    resource: Option<String>,
+    file: String,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+impl Location {
+    fn into_frame(self, function: Option<String>) -> StackFrame {
+        StackFrame {
+            function,
+            resource: self.resource,
+            file: self.file,
+            line: self.line,
+            column: self.column,
+            original: None,
+        }
+    }
+}
+
+/// `@myres/server/main.lua:42`, `/abs/path.js:1:7`, `C:\path\Main.cs:42` or `Main.cs:line 42`.
+fn parse_location(text: &str) -> Option<Location> {
+    let mut path = text.trim();
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        let Some((rest, tail)) = path.rsplit_once(':') else {
+            break;
+        };
+        let tail = tail.trim().strip_prefix("line ").unwrap_or(tail.trim());
+        match tail.parse::<u32>() {
+            Ok(number) => {
+                numbers.push(number);
+                path = rest;
+            }
+            Err(_) => break,
+        }
+    }
+    let (line, column) = match numbers.as_slice() {
+        [column, line] => (Some(*line), Some(*column)),
+        [line] => (Some(*line), None),
+        _ => (None, None),
+    };
+
+    let (resource, file) = match path.strip_prefix('@').and_then(|p| p.split_once('/')) {
+        Some((resource, file)) if !resource.is_empty() && !resource.contains(':') => {
+            (Some(resource.to_string()), file.to_string())
+        }
+        _ => (None, path.to_string()),
+    };
+    if file.is_empty() || file.starts_with(['<', '[']) {
+        return None;
+    }
+    Some(Location { resource, file, line, column })
+}
+
+/// Splits `@myres/main.lua:42: message` after the line number.
+fn split_lua_location(text: &str) -> Option<(&str, &str)> {
+    for (i, _) in text.match_indices(':') {
+        let after = &text[i + 1..];
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && after[digits..].starts_with(": ") {
+            return Some((&text[..i + 1 + digits], &after[digits + 2..]));
+        }
+    }
+    None
+}
+
+fn language_of(file: &str, default: ScriptLanguage) -> ScriptLanguage {
+    match file.rsplit('.').next() {
+        Some("lua") => ScriptLanguage::Lua,
+        Some("js" | "mjs" | "cjs" | "ts") => ScriptLanguage::JavaScript,
+        Some("cs") => ScriptLanguage::CSharp,
+        _ => default,
+    }
+}
+
+fn lua_header(text: &str, channel: Option<&str>) -> Option<ScriptError> {
+    let (resource, rest) = if let Some((_, rest)) = text.split_once("SCRIPT ERROR: ") {
+        (None, rest)
+    } else {
+        // `Error loading script server.lua in resource myres: @myres/server.lua:3: ...`
+        let rest = text.trim_start().strip_prefix("Error loading script ")?;
+        let (_, rest) = rest.split_once(" in resource ")?;
+        let (resource, rest) = rest.split_once(": ")?;
+        (Some(resource.to_string()), rest)
+    };
+
+    let (location, message) = match split_lua_location(rest) {
+        Some((location, message)) => (parse_location(location), message),
+        None => (None, rest),
+    };
+    Some(ScriptError {
+        language: location
+            .as_ref()
+            .map(|l| language_of(&l.file, ScriptLanguage::Lua))
+            .unwrap_or(ScriptLanguage::Lua),
+        resource: location
+            .as_ref()
+            .and_then(|l| l.resource.clone())
+            .or(resource)
+            .or_else(|| channel_resource(channel)),
+        file: location.as_ref().map(|l| l.file.clone()),
+        line: location.as_ref().and_then(|l| l.line),
+        column: location.and_then(|l| l.column),
+        message: message.trim().to_string(),
+        frames: Vec::new(),
+        original: None,
+    })
+}
+
+/// `Error: boom`, `TypeError: x is not a function` or `System.NullReferenceException: ...`
+/// at the start of the line, possibly after a `Failed to run a tick for Main: ` prefix.
+/// Printed text like `ValidationError: bad input` only counts once an `at` frame follows.
+fn exception_header(text: &str, channel: Option<&str>) -> Option<ScriptError> {
+    let text = text.trim();
+    let text = match text.strip_prefix("Failed to run a tick for ").and_then(|rest| rest.split_once(": ")) {
+        Some((_, rest)) => rest,
+        None => text,
+    };
+    let (name, _) = text.split_once(": ")?;
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '_') {
+        return None;
+    }
+    let language = if name.ends_with("Exception") {
+        ScriptLanguage::CSharp
+    } else if name.ends_with("Error") && !name.contains('.') {
+        ScriptLanguage::JavaScript
+    } else {
+        return None;
+    };
+    Some(ScriptError {
+        language,
+        resource: channel_resource(channel),
+        file: None,
+        line: None,
+        column: None,
+        message: text.to_string(),
+        frames: Vec::new(),
+        original: None,
+    })
+}
+
+/// Whether `text` belongs to the stack trace of an error, and the frame it describes if any.
+fn frame_line(text: &str, language: ScriptLanguage) -> Option<Option<StackFrame>> {
+    let text = text.trim();
+    if language == ScriptLanguage::Lua {
+        if text == "stack traceback:" {
+            return Some(None);
+        }
+        // FiveM: `> handler (@myres/main.lua:42)`
+        if let Some(frame) = text.strip_prefix("> ") {
+            let (function, location) = match frame.rsplit_once(" (") {
+                Some((function, location)) => (Some(function), location.strip_suffix(')').unwrap_or(location)),
+                None => (None, frame),
+            };
+            return Some(parse_location(location).map(|l| l.into_frame(function.map(String::from))));
+        }
+        // Plain Lua: `@myres/main.lua:42: in function 'handler'` or `[C]: in ?`
+        let (location, function) = text.split_once(": in ")?;
+        return Some(parse_location(location).map(|l| l.into_frame(Some(function.to_string()))));
+    }
+
+    let frame = text.strip_prefix("at ")?;
+    if language == ScriptLanguage::CSharp {
+        // `at MyRes.Main.OnTick () [0x00001] in C:\src\Main.cs:42`
+        let (function, location) = match frame.split_once(" in ") {
+            Some((function, location)) => (function, Some(location)),
+            None => (frame, None),
+        };
+        let function = function.split(" [0x").next().unwrap_or(function).trim();
+        let Some(location) = location.and_then(parse_location) else {
+            return Some(None);
+        };
+        return Some(Some(location.into_frame(Some(function.to_string()))));
+    }
+
+    // `at handler (@myres/server.js:1:7)` or `at @myres/server.js:1:7`
+    let (function, location) = match frame.strip_suffix(')').and_then(|f| f.rsplit_once(" (")) {
+        Some((function, location)) => (Some(function), location),
+        None => (None, frame),
+    };
+    Some(parse_location(location).map(|l| l.into_frame(function.map(String::from))))
+}
+
+/// Gathers script errors from console lines fed one at a time. An error is
+/// complete once a line that is not part of its stack trace arrives, or on
+/// [`ScriptErrorParser::finish`].
+#[derive(Debug, Default)]
+pub struct ScriptErrorParser {
+    current: Option<ScriptError>,
+    /// Whether a line of the current error's stack trace came, JS and C# errors need one
+    traced: bool,
+}
+
+impl ScriptErrorParser {
+    /// Feeds a line that still starts with its `[channel]`, as in command replies.
+    pub fn push_line(&mut self, line: &str) -> Option<ScriptError> {
+        let line = strip_colors(line);
+        let (channel, text) = split_channel(&line);
+        self.push(channel, text)
+    }
+
+    /// Feeds a line whose channel is known, as with the console listener of FXServer.
+    pub fn push(&mut self, channel: Option<&str>, line: &str) -> Option<ScriptError> {
+        let text = strip_colors(line);
+        let text = text.as_str();
+
+        if let Some(current) = &mut self.current {
+            if let Some(frame) = frame_line(text, current.language) {
+                current.frames.extend(frame);
+                self.traced = true;
+                return None;
+            }
+        }
+
+        let header = lua_header(text, channel).or_else(|| exception_header(text, channel));
+        let finished = self.finish();
+        self.current = header;
+        finished
+    }
+
+    pub fn finish(&mut self) -> Option<ScriptError> {
+        let mut error = self.current.take()?;
+        let traced = std::mem::take(&mut self.traced);
+        if error.language != ScriptLanguage::Lua && !traced {
+            return None;
+        }
+        // JS and C# headers carry no position, the innermost frame of the resource has it
+        if error.file.is_none() {
+            let frame = error
+                .frames
+                .iter()
+                .find(|f| f.resource.is_some() && (error.resource.is_none() || f.resource == error.resource))
+                .or_else(|| error.frames.first());
+            if let Some(frame) = frame {
+                error.file = Some(frame.file.clone());
+                error.line = frame.line;
+                error.column = frame.column;
+                if error.resource.is_none() {
+                    error.resource = frame.resource.clone();
+                }
+            }
+        }
+        Some(error)
+    }
+}
+
+/// Every script error of a block of console output.
+pub fn parse_script_errors(text: &str) -> Vec<ScriptError> {
+    let mut parser = ScriptErrorParser::default();
+    let mut errors: Vec<ScriptError> = text.lines().filter_map(|line| parser.push_line(line)).collect();
+    errors.extend(parser.finish());
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lua_error_with_traceback() {
+        let output = "\
+[    script:myres] SCRIPT ERROR: @myres/server/main.lua:42: attempt to index a nil value (field 'player')
+[    script:myres] > handler (@myres/server/main.lua:42)
+[    script:myres] > fn (@myres/server/events.lua:7)
+[    script:myres] [C]: in ?
+[      resources] Started resource other";
+        let errors = parse_script_errors(output);
+        assert_eq!(errors.len(), 1);
+        let error = &errors[0];
+        assert_eq!(error.language, ScriptLanguage::Lua);
+        assert_eq!(error.resource.as_deref(), Some("myres"));
+        assert_eq!((error.file.as_deref(), error.line), (Some("server/main.lua"), Some(42)));
+        assert_eq!(error.message, "attempt to index a nil value (field 'player')");
+        assert_eq!(error.frames.len(), 2);
+        assert_eq!(error.frames[1].function.as_deref(), Some("fn"));
+        assert_eq!(error.frames[1].file, "server/events.lua");
+    }
+
+    #[test]
+    fn lua_plain_traceback_skips_c_frames() {
+        let output = "\
+[script:myres] SCRIPT ERROR: @myres/client.lua:3: boom
+[script:myres] stack traceback:
+[script:myres] 	[C]: in function 'error'
+[script:myres] 	@myres/client.lua:3: in function 'explode'";
+        let error = &parse_script_errors(output)[0];
+        assert_eq!(error.frames.len(), 1);
+        assert_eq!(error.frames[0].function.as_deref(), Some("function 'explode'"));
+        assert_eq!(error.frames[0].line, Some(3));
+    }
+
+    #[test]
+    fn lua_loading_error_names_the_resource() {
+        let output = "Error loading script server.lua in resource myres: @myres/server.lua:3: '=' expected near 'x'";
+        let error = &parse_script_errors(output)[0];
+        assert_eq!(error.resource.as_deref(), Some("myres"));
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.message, "'=' expected near 'x'");
+    }
+
+    #[test]
+    fn js_error_takes_its_position_from_the_first_resource_frame() {
+        let output = "\
+[    script:jsres] TypeError: Cannot read properties of undefined (reading 'id')
+[    script:jsres]     at getPlayer (@jsres/dist/server.js:12:20)
+[    script:jsres]     at @jsres/dist/server.js:40:5
+[    script:jsres]     at processTicksAndRejections (node:internal/process/task_queues:95:5)";
+        let error = &parse_script_errors(output)[0];
+        assert_eq!(error.language, ScriptLanguage::JavaScript);
+        assert_eq!(error.resource.as_deref(), Some("jsres"));
+        assert_eq!((error.file.as_deref(), error.line, error.column), (Some("dist/server.js"), Some(12), Some(20)));
+        assert_eq!(error.message, "TypeError: Cannot read properties of undefined (reading 'id')");
+        assert_eq!(error.frames.len(), 3);
+    }
+
+    #[test]
+    fn csharp_exception_after_a_tick_prefix() {
+        let output = "\
+[    script:csres] Failed to run a tick for Main: System.NullReferenceException: Object reference not set to an instance of an object.
+[    script:csres]   at CsRes.Server.Main.OnTick () [0x00012] in C:\\src\\CsRes\\Main.cs:42
+[    script:csres]   at CitizenFX.Core.BaseScript.ScheduleRun () [0x00000] in <00000000000000000000000000000000>:0";
+        let error = &parse_script_errors(output)[0];
+        assert_eq!(error.language, ScriptLanguage::CSharp);
+        assert_eq!(error.resource.as_deref(), Some("csres"));
+        assert_eq!(error.message, "System.NullReferenceException: Object reference not set to an instance of an object.");
+        assert_eq!(error.frames.len(), 1);
+        assert_eq!(error.frames[0].function.as_deref(), Some("CsRes.Server.Main.OnTick ()"));
+        assert_eq!((error.file.as_deref(), error.line), (Some("C:\\src\\CsRes\\Main.cs"), Some(42)));
+    }
+
+    #[test]
+    fn csharp_line_suffix_location() {
+        let output = "\
+[script:csres] System.InvalidOperationException: nope
+[script:csres]    at CsRes.Main.Run() in Main.cs:line 42";
+        let error = &parse_script_errors(output)[0];
+        assert_eq!((error.file.as_deref(), error.line), (Some("Main.cs"), Some(42)));
+    }
+
+    #[test]
+    fn printed_error_names_are_not_errors() {
+        let output = "\
+[    script:myres] ValidationError: bad input
+[    script:myres] player said: TypeError: not really
+[    script:myres] done";
+        assert!(parse_script_errors(output).is_empty());
+    }
+
+    #[test]
+    fn strips_color_codes() {
+        assert_eq!(strip_colors("^1SCRIPT ERROR: ^7boom\u{1b}[0m"), "SCRIPT ERROR: boom");
+    }
+}
@@ -8,6 +8,10 @@ pub struct ConnectionProfile {
     pub ws_url: String,
     pub api_key: String,
     pub is_local: bool,
+    /// Les changements attendent un « Appliquer » au lieu d'être rechargés tout de suite,
+    /// réappliqué au watcher à chaque connexion avec ce profil
+    #[serde(default)]
+    pub manual_apply: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -17,6 +21,13 @@ pub struct ServerConfig {
     pub profiles: Vec<ConnectionProfile>,
     pub language: Language,
     pub theme: String,
+    /// Nombre de lignes de la console gardées en mémoire
+    #[serde(default = "default_console_scrollback")]
+    pub console_scrollback: usize,
+}
+
+fn default_console_scrollback() -> usize {
+    1000
 }
 
 impl ConnectionProfile {
@@ -27,6 +38,7 @@ impl ConnectionProfile {
             ws_url,
             api_key,
             is_local,
+            manual_apply: false,
         }
     }
 }
@@ -41,9 +53,11 @@ impl Default for ServerConfig {
                 ws_url: String::from("ws://localhost:3090"),
                 api_key: String::new(),
                 is_local: true,
+                manual_apply: false,
             }],
             language: Language::English,
             theme: "dark".to_string(),
+            console_scrollback: default_console_scrollback(),
         }
     }
 }
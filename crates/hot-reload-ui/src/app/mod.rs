@@ -11,11 +11,12 @@ use eframe::egui::ImageSource;
 use eframe::{egui, App, Theme};
 use futures_util::{SinkExt, StreamExt};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::collections::VecDeque;
-use hot_reload_common::{AuthRequest, AuthResponse, InitialData};
+use hot_reload_common::{ActionState, ActionStatus, BreakerState, SnapshotInfo, HistoryPage, AuthRequest, AuthResponse, ClientMessage, ConsoleLine, Diagnostic, OutputStream, ApplyMode, PauseState, PendingChanges, ScriptError, InitialData, PipelineLog, ReloadReason, ReloadStrategy, ResourceChange, ResourceState, Role, Severity, ResourceGraph, ScanReport, TestReport, TreeDelta, WatcherMessage, WatcherStats};
+use tokio::sync::mpsc;
 
 #[derive(Clone, PartialEq)]
 enum ConnectionStatus {
@@ -32,6 +33,9 @@ pub struct HotReloadApp {
     connection_status: Arc<Mutex<ConnectionStatus>>,
     resource_tree: Arc<Mutex<HashMap<String, Vec<String>>>>,
     resources_path: Arc<Mutex<Option<String>>>,
+    watcher_data: Arc<Mutex<WatcherData>>,
+    /// Canal vers la connexion active, vide tant qu'on n'est pas connecté
+    outgoing: Arc<Mutex<Option<mpsc::UnboundedSender<String>>>>,
     show_add_profile_popup: bool,
     show_api_key_popup: bool,
     new_profile_name: String,
@@ -42,6 +46,14 @@ pub struct HotReloadApp {
     show_hidden_files: bool,
     show_about_popup: bool,
     tree_state: ResourceTreeState,
+    main_tab: MainTab,
+    graph_view: GraphView,
+    strategy_editor: StrategyEditor,
+    test_view: TestView,
+    diagnostics_view: DiagnosticsView,
+    console_view: ConsoleView,
+    pause_form: PauseForm,
+    history_view: HistoryView,
     icons: Option<FileIcons>,
     translator: Translator,
     is_connected: bool,
@@ -51,10 +63,207 @@ pub struct HotReloadApp {
     last_update: Arc<Mutex<std::time::Instant>>,
 }
 
+/// Données poussées par le watcher en dehors de l'arbre des ressources
+#[derive(Default, Clone, Debug)]
+struct WatcherData {
+    scan_report: Option<ScanReport>,
+    stats: Option<WatcherStats>,
+    /// Dernier rechargement demandé par le watcher pour chaque ressource
+    last_reloads: HashMap<String, ResourceChange>,
+    graph: Option<ResourceGraph>,
+    strategies: HashMap<String, ReloadStrategy>,
+    /// Résultat des derniers tests lancés après un rechargement
+    test_reports: HashMap<String, TestReport>,
+    /// Diagnostiques des linters par (ressource, fichier, linter), vidés quand le fichier est corrigé
+    diagnostics: BTreeMap<(String, String, String), Vec<Diagnostic>>,
+    /// Erreurs de scripts relevées par le watcher, les plus récentes en premier
+    script_errors: VecDeque<ScriptError>,
+    /// Console de FXServer, limitée à `console_scrollback` lignes
+    console: VecDeque<ConsoleLine>,
+    console_scrollback: usize,
+    /// Rôle accordé par le watcher, seuls les opérateurs envoient des commandes
+    role: Role,
+    /// Dernière action manuelle lancée sur chaque ressource depuis l'arbre
+    actions: HashMap<String, ActionStatus>,
+    /// État des ressources sur le serveur, vide tant que FXServer n'a pas répondu
+    resource_states: HashMap<String, ResourceState>,
+    /// Pause des rechargements automatiques, partagée par tous les clients
+    pause: PauseState,
+    /// Mode de rechargement et changements en attente d'un « Appliquer »
+    pending: PendingChanges,
+    /// Disjoncteurs des ressources qui ont déjà échoué, le rechargement est coupé pour celles déclenchées
+    breakers: HashMap<String, BreakerState>,
+    /// Instantanés gardés par le watcher pour chaque ressource, du plus récent au plus ancien
+    snapshots: HashMap<String, Vec<SnapshotInfo>>,
+    /// Nom de ce client dans l'historique du watcher
+    user: String,
+    /// Dernière page de l'historique demandée depuis l'onglet
+    history: Option<HistoryPage>,
+}
+
+const SCRIPT_ERRORS_CAPACITY: usize = 100;
+
+/// Saisie en cours dans l'éditeur des stratégies de rechargement
+#[derive(Default)]
+struct StrategyEditor {
+    filter: String,
+    custom_commands: HashMap<String, String>,
+}
+
+/// Ressource à déplier dans le panneau des tests, choisie depuis l'arbre
+#[derive(Default)]
+struct TestView {
+    focus: Option<String>,
+}
+
+/// Console en pause (copie figée des lignes), défilement, filtre et saisie des commandes
+struct ConsoleView {
+    paused: Option<VecDeque<ConsoleLine>>,
+    autoscroll: bool,
+    filter: String,
+    input: String,
+    /// Commandes envoyées, la plus récente en dernier
+    history: Vec<String>,
+    /// Position dans l'historique pendant la navigation avec ↑/↓
+    history_pos: Option<usize>,
+    /// Ressources proposées par la dernière complétion ambiguë
+    completions: Vec<String>,
+    next_command_id: u64,
+}
+
+impl Default for ConsoleView {
+    fn default() -> Self {
+        Self {
+            paused: None,
+            autoscroll: true,
+            filter: String::new(),
+            input: String::new(),
+            history: Vec::new(),
+            history_pos: None,
+            completions: Vec::new(),
+            next_command_id: 1,
+        }
+    }
+}
+
+/// Fenêtre de mise en pause : raison et durée en minutes, 0 pour une pause sans fin
+#[derive(Default)]
+struct PauseForm {
+    open: bool,
+    reason: String,
+    minutes: u32,
+}
+
+/// Période couverte par l'onglet historique
+#[derive(Default, Clone, Copy, PartialEq)]
+enum HistoryRange {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    All,
+}
+
+impl HistoryRange {
+    const ALL: [HistoryRange; 4] = [HistoryRange::Hour, HistoryRange::Day, HistoryRange::Week, HistoryRange::All];
+
+    fn key(self) -> &'static str {
+        match self {
+            HistoryRange::Hour => "history_range_hour",
+            HistoryRange::Day => "history_range_day",
+            HistoryRange::Week => "history_range_week",
+            HistoryRange::All => "history_range_all",
+        }
+    }
+
+    fn millis(self) -> Option<u64> {
+        match self {
+            HistoryRange::Hour => Some(3_600_000),
+            HistoryRange::Day => Some(86_400_000),
+            HistoryRange::Week => Some(7 * 86_400_000),
+            HistoryRange::All => None,
+        }
+    }
+}
+
+/// Filtres de l'onglet historique, la recherche part vers le watcher
+#[derive(Default)]
+struct HistoryView {
+    range: HistoryRange,
+    resource: String,
+    user: String,
+    /// Première recherche faite à l'ouverture de l'onglet
+    requested: bool,
+}
+
+/// Filtres du panneau des diagnostics
+struct DiagnosticsView {
+    severities: Vec<Severity>,
+    filter: String,
+}
+
+impl Default for DiagnosticsView {
+    fn default() -> Self {
+        Self {
+            severities: vec![Severity::Error, Severity::Warning, Severity::Info],
+            filter: String::new(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
+enum MainTab {
+    #[default]
+    HotReload,
+    Graph,
+    Diagnostics,
+    Console,
+    Pending,
+    History,
+}
+
+/// État de la vue du graphe de dépendances
+struct GraphView {
+    offset: egui::Vec2,
+    zoom: f32,
+    selected: Option<String>,
+    positions: HashMap<String, egui::Pos2>,
+    /// Graphe utilisé pour calculer `positions`, recalculées quand il change
+    layout_for: Option<ResourceGraph>,
+}
+
+impl Default for GraphView {
+    fn default() -> Self {
+        Self {
+            offset: egui::Vec2::new(20.0, 20.0),
+            zoom: 1.0,
+            selected: None,
+            positions: HashMap::new(),
+            layout_for: None,
+        }
+    }
+}
+
+/// Ressources affichées dans l'arbre selon leur état sur le serveur
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Debug)]
+enum TreeFilter {
+    #[default]
+    All,
+    /// Sur le disque mais pas chargées par le serveur
+    NotLoaded,
+    /// Chargées par le serveur sans fichier surveillé sur le disque
+    LoadedWithoutFiles,
+}
+
 #[derive(Default, Clone, Serialize, Debug)]
 struct ResourceTreeState {
     expanded: HashMap<String, bool>,
     checked: HashMap<String, bool>,
+    /// Ressource ou `ressource/fichier` ouvert depuis un diagnostic
+    highlighted: Option<String>,
+    #[serde(skip)]
+    scroll_to_highlight: bool,
+    filter: TreeFilter,
 }
 
 struct FileIcons {
@@ -113,6 +322,8 @@ impl HotReloadApp {
             connection_status,
             resource_tree,
             resources_path,
+            watcher_data: Arc::new(Mutex::new(WatcherData::default())),
+            outgoing: Arc::new(Mutex::new(None)),
             show_add_profile_popup: false,
             show_api_key_popup: false,
             new_profile_name: String::new(),
@@ -122,6 +333,14 @@ impl HotReloadApp {
             show_hidden_files: false,
             show_about_popup: false,
             tree_state: ResourceTreeState::default(),
+            main_tab: MainTab::default(),
+            graph_view: GraphView::default(),
+            strategy_editor: StrategyEditor::default(),
+            test_view: TestView::default(),
+            diagnostics_view: DiagnosticsView::default(),
+            console_view: ConsoleView::default(),
+            pause_form: PauseForm::default(),
+            history_view: HistoryView::default(),
             icons: Some(icons),
             translator,
             theme: Theme::Dark,
@@ -144,6 +363,9 @@ impl HotReloadApp {
                 },
             };
         }
+        if let Ok(mut data) = app.watcher_data.lock() {
+            data.console_scrollback = app.config.console_scrollback;
+        }
 
         app
     }
@@ -163,8 +385,16 @@ impl HotReloadApp {
         let status = self.connection_status.clone();
         let resource_tree = self.resource_tree.clone();
         let resources_path = self.resources_path.clone();
+        let watcher_data = self.watcher_data.clone();
         let logs = self.logs.clone();
         let pending_messages = self.pending_messages.clone();
+        let outgoing = self.outgoing.clone();
+        // Nom donné au watcher pour l'historique : l'utilisateur du système, sinon le profil
+        let user_name = std::env::var("USERNAME")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_else(|_| self.config.current_profile.clone().unwrap_or_default());
+        // Le watcher n'a qu'un mode pour tous ses clients, un profil en manuel le remet en manuel en se connectant
+        let manual_apply = self.config.get_current_profile().is_some_and(|profile| profile.manual_apply);
 
         rt.spawn(async move {
             info!("🔌 Tentative de connexion à {}", ws_url);
@@ -209,11 +439,40 @@ impl HotReloadApp {
                     if let Ok(mut status) = status.lock() {
                         *status = ConnectionStatus::Connected;
                     }
+                    // Le watcher renvoie les dernières lignes de la console à chaque connexion
+                    if let Ok(mut data) = watcher_data.lock() {
+                        data.console.clear();
+                    }
 
                     info!("📡 Connexion WebSocket établie");
 
+                    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+                    if let Ok(text) = serde_json::to_string(&ClientMessage::Hello { user: user_name }) {
+                        let _ = outgoing_tx.send(text);
+                    }
+                    if manual_apply {
+                        if let Ok(text) = serde_json::to_string(&ClientMessage::SetApplyMode { mode: ApplyMode::Manual }) {
+                            let _ = outgoing_tx.send(text);
+                        }
+                    }
+                    if let Ok(mut sender) = outgoing.lock() {
+                        *sender = Some(outgoing_tx);
+                    }
+
                     // Boucle de réception des messages
-                    while let Some(msg) = ws_stream.next().await {
+                    loop {
+                        let msg = tokio::select! {
+                            msg = ws_stream.next() => match msg {
+                                Some(msg) => msg,
+                                None => break,
+                            },
+                            Some(text) = outgoing_rx.recv() => {
+                                if let Err(e) = ws_stream.send(Message::Text(text)).await {
+                                    error!("❌ Erreur d'envoi au watcher: {}", e);
+                                }
+                                continue;
+                            }
+                        };
                         match msg {
                             Ok(msg) => {
                                 if let Ok(text) = msg.to_text() {
@@ -229,6 +488,12 @@ impl HotReloadApp {
                                             continue;
                                         }
                                         Err(_) => {
+                                            // Essayer de parser en tant que message typé du watcher
+                                            if let Ok(message) = serde_json::from_str::<WatcherMessage>(text) {
+                                                Self::handle_watcher_message(&watcher_data, &resource_tree, &logs, message);
+                                                continue;
+                                            }
+
                                             // Essayer de parser en tant que message batch
                                             match serde_json::from_str::<serde_json::Value>(text) {
                                                 Ok(json) => {
@@ -260,6 +525,10 @@ impl HotReloadApp {
                             }
                         }
                     }
+
+                    if let Ok(mut sender) = outgoing.lock() {
+                        *sender = None;
+                    }
                 }
                 Err(e) => {
                     error!("❌ Erreur de connexion: {}", e);
@@ -287,6 +556,264 @@ impl HotReloadApp {
         }
     }
 
+    fn handle_watcher_message(
+        watcher_data: &Arc<Mutex<WatcherData>>,
+        resource_tree: &Arc<Mutex<HashMap<String, Vec<String>>>>,
+        logs: &Arc<Mutex<VecDeque<String>>>,
+        message: WatcherMessage,
+    ) {
+        let Ok(mut data) = watcher_data.lock() else {
+            return;
+        };
+        match message {
+            WatcherMessage::ScanReport(report) => {
+                info!("🩺 Rapport de scan reçu: {} problème(s)", report.diagnostics.len());
+                data.scan_report = Some(report);
+            }
+            WatcherMessage::Stats(stats) => {
+                data.stats = Some(stats);
+            }
+            WatcherMessage::TreeDelta(delta) => {
+                info!("🌳 Delta reçu pour {}: +{} -{}", delta.resource_name, delta.added.len(), delta.removed.len());
+                Self::apply_tree_delta(resource_tree, delta);
+            }
+            WatcherMessage::Resync(report) => {
+                info!("🔄 Resynchronisation du watcher: {}", report.reason);
+                Self::push_log(logs, format!(
+                    "🔄 Resync ({}): {} resource(s), {} file(s) changed",
+                    report.reason,
+                    report.resources.len(),
+                    report.changed_files
+                ));
+            }
+            WatcherMessage::Reload(change) => {
+                let reason = match &change.reason {
+                    ReloadReason::FileChanged => "file changed".to_string(),
+                    ReloadReason::Dependency { resource } => format!("depends on {}", resource),
+                    ReloadReason::Import { resource, file } => format!("loads @{}/{}", resource, file),
+                    ReloadReason::Manual { action } => format!("manual {}", action.name()),
+                };
+                Self::push_log(logs, format!("🔁 {} ({})", change.resource_name, reason));
+                data.last_reloads.insert(change.resource_name.clone(), change);
+            }
+            WatcherMessage::Graph(graph) => {
+                info!("🕸️ Graphe de dépendances reçu: {} ressource(s)", graph.nodes.len());
+                data.graph = Some(graph);
+            }
+            WatcherMessage::ReloadStrategies(table) => {
+                info!("🛠️ Stratégies de rechargement reçues: {}", table.strategies.len());
+                if let Some(error) = &table.error {
+                    Self::push_log(logs, format!("❌ config.hotwatch.json: {}", error));
+                }
+                data.strategies = table.strategies;
+            }
+            WatcherMessage::BuildStarted(build) => {
+                Self::push_log(logs, format!("🔨 [{}] {}", build.resource, build.command));
+            }
+            WatcherMessage::BuildOutput(output) => {
+                let prefix = match output.stream {
+                    OutputStream::Stdout => "",
+                    OutputStream::Stderr => "⚠ ",
+                };
+                Self::push_log(logs, format!("[{}] {}{}", output.resource, prefix, output.line));
+            }
+            WatcherMessage::BuildFinished(build) => {
+                let message = match &build.error {
+                    None => format!("✅ [{}] Build OK ({} ms)", build.resource, build.duration_ms),
+                    Some(error) => format!("❌ [{}] Build failed, reload cancelled: {}", build.resource, error),
+                };
+                Self::push_log(logs, message);
+            }
+            WatcherMessage::TestReport(report) => {
+                let message = match &report.error {
+                    _ if report.success => format!(
+                        "🧪 [{}] Tests OK: {} passed, {} skipped ({} ms)",
+                        report.resource, report.passed, report.skipped, report.duration_ms
+                    ),
+                    Some(error) => format!("🧪 [{}] Tests failed: {} failed, {}", report.resource, report.failed, error),
+                    None => format!("🧪 [{}] Tests failed: {} failed, {} passed", report.resource, report.failed, report.passed),
+                };
+                Self::push_log(logs, message);
+                data.test_reports.insert(report.resource.clone(), report);
+            }
+            WatcherMessage::Lint(report) => {
+                let key = (report.resource, report.file, report.source);
+                if report.diagnostics.is_empty() {
+                    data.diagnostics.remove(&key);
+                } else {
+                    data.diagnostics.insert(key, report.diagnostics);
+                }
+            }
+            WatcherMessage::ScriptError(script_error) => {
+                Self::push_log(logs, format!(
+                    "⛔ [{}] {}",
+                    script_error.resource.as_deref().unwrap_or("?"),
+                    script_error.message
+                ));
+                if data.script_errors.len() >= SCRIPT_ERRORS_CAPACITY {
+                    data.script_errors.pop_back();
+                }
+                data.script_errors.push_front(script_error);
+            }
+            WatcherMessage::Console(line) => {
+                Self::push_line(&mut data, line);
+            }
+            WatcherMessage::Session(session) => {
+                info!("🔐 Rôle accordé par le watcher: {:?}", session.role);
+                data.role = session.role;
+                data.user = session.user;
+            }
+            WatcherMessage::CommandResult(result) => {
+                let message = match &result.error {
+                    None => format!("⌨️ {} ({} line(s))", result.command, result.output.len()),
+                    Some(error) => format!("❌ {}: {}", result.command, error),
+                };
+                Self::push_log(logs, message);
+                if let Some(error) = result.error {
+                    Self::push_console(&mut data, "hot-reload", format!("^1{}: {}^7", result.command, error));
+                }
+            }
+            WatcherMessage::ActionStatus(status) => {
+                let message = status.message.as_deref().unwrap_or_default();
+                match status.state {
+                    ActionState::Succeeded => Self::push_log(logs, format!("✅ [{}] {}: {}", status.resource, status.action.name(), message)),
+                    ActionState::Failed => Self::push_log(logs, format!("❌ [{}] {}: {}", status.resource, status.action.name(), message)),
+                    ActionState::Queued | ActionState::Running => {}
+                }
+                data.actions.insert(status.resource.clone(), status);
+            }
+            WatcherMessage::ResourceStates(table) => {
+                info!("🚦 États des ressources reçus: {}", table.states.len());
+                data.resource_states = table.states;
+            }
+            WatcherMessage::ResourceStateChange(change) => {
+                info!("🚦 {} est maintenant {:?}", change.resource, change.state);
+                data.resource_states.insert(change.resource, change.state);
+            }
+            WatcherMessage::Pause(pause) => {
+                if pause.paused != data.pause.paused {
+                    let message = match (pause.paused, &pause.reason) {
+                        (true, Some(reason)) => format!("⏸️ Auto-reload paused: {}", reason),
+                        (true, None) => "⏸️ Auto-reload paused".to_string(),
+                        (false, _) => "▶️ Auto-reload resumed".to_string(),
+                    };
+                    Self::push_log(logs, message);
+                }
+                data.pause = pause;
+            }
+            WatcherMessage::PendingChanges(pending) => {
+                info!("🧺 {} changement(s) en attente ({:?})", pending.changes.len(), pending.mode);
+                data.pending = pending;
+            }
+            WatcherMessage::Breaker(breaker) => {
+                let was_tripped = data.breakers.get(&breaker.resource).is_some_and(|previous| previous.tripped);
+                match (was_tripped, breaker.tripped) {
+                    (false, true) => Self::push_log(logs, format!(
+                        "⛔ [{}] Auto-reload stopped after {} failures: {}",
+                        breaker.resource,
+                        breaker.failures,
+                        breaker.last_error.as_deref().unwrap_or("-")
+                    )),
+                    (true, false) => Self::push_log(logs, format!("🔌 [{}] Auto-reload enabled again", breaker.resource)),
+                    _ => {}
+                }
+                data.breakers.insert(breaker.resource.clone(), breaker);
+            }
+            WatcherMessage::Snapshots(list) => {
+                info!("📸 {} instantané(s) de {}", list.snapshots.len(), list.resource);
+                data.snapshots.insert(list.resource, list.snapshots);
+            }
+            WatcherMessage::History(page) => {
+                info!("📜 Historique reçu: {} entrée(s)", page.entries.len());
+                data.history = Some(page);
+            }
+            WatcherMessage::PipelineLog(log) => {
+                info!("🧾 Journal du pipeline reçu: {} entrée(s)", log.entries.len());
+                Self::dump_pipeline_log(logs, &log);
+            }
+        }
+    }
+
+    fn dump_pipeline_log(logs: &Arc<Mutex<VecDeque<String>>>, log: &PipelineLog) {
+        let Ok(json) = serde_json::to_string_pretty(log) else {
+            return;
+        };
+        let debug_file = format!(
+            "debug_pipeline_{}.json",
+            Local::now().format("%Y%m%d_%H%M%S")
+        );
+        match std::fs::write(&debug_file, json) {
+            Ok(_) => Self::push_log(logs, format!("🧾 Pipeline log dumped to {}", debug_file)),
+            Err(e) => error!("❌ Impossible d'écrire {}: {}", debug_file, e),
+        }
+    }
+
+    fn watcher_connected(&self) -> bool {
+        self.outgoing.lock().map(|sender| sender.is_some()).unwrap_or(false)
+    }
+
+    /// Envoie un message au watcher si une connexion est ouverte
+    fn send_to_watcher(&self, message: &ClientMessage) -> bool {
+        let Ok(text) = serde_json::to_string(message) else {
+            return false;
+        };
+        match self.outgoing.lock() {
+            Ok(sender) => sender.as_ref().map(|tx| tx.send(text).is_ok()).unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    fn apply_tree_delta(
+        resource_tree: &Arc<Mutex<HashMap<String, Vec<String>>>>,
+        delta: TreeDelta,
+    ) {
+        let Ok(mut tree) = resource_tree.lock() else {
+            return;
+        };
+        let files = tree.entry(delta.resource_name.clone()).or_default();
+        files.retain(|file| !delta.removed.contains(file));
+        for file in delta.added {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        files.sort_by_cached_key(|file| file.to_lowercase());
+
+        // Une ressource sans fichier surveillé n'apparaît pas dans l'arbre
+        if files.is_empty() {
+            tree.remove(&delta.resource_name);
+        }
+    }
+
+    fn push_line(data: &mut WatcherData, line: ConsoleLine) {
+        while data.console.len() >= data.console_scrollback.max(1) {
+            data.console.pop_front();
+        }
+        data.console.push_back(line);
+    }
+
+    /// Ligne ajoutée par l'interface elle-même, comme l'écho des commandes
+    fn push_console(data: &mut WatcherData, channel: &str, message: String) {
+        let timestamp_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self::push_line(data, ConsoleLine {
+            channel: channel.to_string(),
+            message,
+            timestamp_ms,
+        });
+    }
+
+    fn push_log(logs: &Arc<Mutex<VecDeque<String>>>, message: String) {
+        if let Ok(mut logs) = logs.lock() {
+            if logs.len() >= 100 {
+                logs.pop_front();
+            }
+            logs.push_back(message);
+        }
+    }
+
     async fn process_message_batch(
         messages: &[String],
         logs: &Arc<Mutex<VecDeque<String>>>,
@@ -397,6 +924,9 @@ impl HotReloadApp {
                 info!("{}", self.translator.t("debug_resources_dumped"));
             }
         }
+
+        // Le journal du pipeline est écrit à la réception de la réponse
+        self.send_to_watcher(&ClientMessage::GetPipelineLog);
     }
 }
 
@@ -1,6 +1,7 @@
 use super::HotReloadApp;
 use crate::app::ConnectionStatus;
 use eframe::egui;
+use hot_reload_common::{ApplyMode, ClientMessage, Role};
 
 impl HotReloadApp {
     pub fn render_header(&mut self, ctx: &egui::Context) {
@@ -65,6 +66,20 @@ impl HotReloadApp {
                             api_changed = ui.add(api_key_edit).changed();
                         }
 
+                        // Préférence du profil, envoyée au watcher dont le mode est partagé par tous ses clients
+                        let mut manual_apply = profile.manual_apply;
+                        let is_operator = self.watcher_data.lock().map(|data| data.role >= Role::Operator).unwrap_or_default();
+                        if ui.checkbox(&mut manual_apply, self.translator.t("apply_mode_manual")).on_hover_text(self.translator.t("apply_mode_hint")).changed() {
+                            if let Some(profile) = self.config.profiles.iter_mut().find(|p| p.name == *current_name) {
+                                profile.manual_apply = manual_apply;
+                            }
+                            self.save_config();
+                            if is_operator && self.watcher_connected() {
+                                let mode = if manual_apply { ApplyMode::Manual } else { ApplyMode::Auto };
+                                self.send_to_watcher(&ClientMessage::SetApplyMode { mode });
+                            }
+                        }
+
                         if ws_changed || api_changed {
                             if let Some(profile) = self
                                 .config
@@ -0,0 +1,280 @@
+use super::HotReloadApp;
+use eframe::egui;
+use eframe::egui::text::LayoutJob;
+use hot_reload_common::{ClientMessage, ConsoleLine, Role};
+
+/// Couleurs des codes `^0`-`^9` de FiveM, `^0` et `^7` reprennent la couleur du thème
+fn fivem_color(code: char, default: egui::Color32) -> egui::Color32 {
+    match code {
+        '1' => egui::Color32::from_rgb(240, 80, 80),
+        '2' => egui::Color32::from_rgb(110, 220, 110),
+        '3' => egui::Color32::from_rgb(240, 200, 60),
+        '4' => egui::Color32::from_rgb(80, 130, 240),
+        '5' => egui::Color32::from_rgb(90, 200, 240),
+        '6' => egui::Color32::from_rgb(200, 110, 230),
+        '8' => egui::Color32::from_rgb(240, 140, 50),
+        '9' => egui::Color32::from_rgb(140, 140, 140),
+        _ => default,
+    }
+}
+
+/// Découpe une ligne sur ses codes couleur, les séquences ANSI sont ignorées
+fn console_job(line: &ConsoleLine, font: egui::FontId, default: egui::Color32, weak: egui::Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let format = |color| egui::TextFormat {
+        font_id: font.clone(),
+        color,
+        ..Default::default()
+    };
+    job.append(&format!("[{:>20}] ", line.channel), 0.0, format(weak));
+
+    let mut color = default;
+    let mut segment = String::new();
+    let mut chars = line.message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '^' if chars.peek().is_some_and(|next| next.is_ascii_digit()) => {
+                if !segment.is_empty() {
+                    job.append(&std::mem::take(&mut segment), 0.0, format(color));
+                }
+                color = fivem_color(chars.next().unwrap_or('0'), default);
+            }
+            '\u{1b}' if chars.peek() == Some(&'[') => {
+                for next in chars.by_ref() {
+                    if next.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            _ => segment.push(c),
+        }
+    }
+    if !segment.is_empty() {
+        job.append(&segment, 0.0, format(color));
+    }
+    job
+}
+
+impl HotReloadApp {
+    pub fn render_console(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let paused = self.console_view.paused.is_some();
+            let label = match paused {
+                true => format!("▶ {}", self.translator.t("console_resume")),
+                false => format!("⏸ {}", self.translator.t("console_pause")),
+            };
+            if ui.button(label).clicked() {
+                // En pause on affiche une copie, les lignes continuent d'arriver derrière
+                self.console_view.paused = match paused {
+                    true => None,
+                    false => self.watcher_data.lock().ok().map(|data| data.console.clone()),
+                };
+            }
+            ui.checkbox(&mut self.console_view.autoscroll, self.translator.t("console_autoscroll"));
+            if ui.button(self.translator.t("console_clear")).clicked() {
+                if let Ok(mut data) = self.watcher_data.lock() {
+                    data.console.clear();
+                }
+                self.console_view.paused = None;
+            }
+            ui.separator();
+            ui.label(self.translator.t("console_scrollback"));
+            let scrollback = ui.add(egui::DragValue::new(&mut self.config.console_scrollback).clamp_range(100..=100_000).speed(100));
+            if scrollback.changed() {
+                if let Ok(mut data) = self.watcher_data.lock() {
+                    data.console_scrollback = self.config.console_scrollback;
+                    while data.console.len() > data.console_scrollback {
+                        data.console.pop_front();
+                    }
+                }
+            }
+            if scrollback.drag_released() || scrollback.lost_focus() {
+                self.save_config();
+            }
+            ui.separator();
+            ui.label("🔍");
+            ui.text_edit_singleline(&mut self.console_view.filter);
+        });
+        ui.separator();
+
+        self.render_console_lines(ui);
+        ui.separator();
+        self.render_console_input(ui);
+    }
+
+    fn render_console_lines(&mut self, ui: &mut egui::Ui) {
+        let Ok(data) = self.watcher_data.lock() else {
+            return;
+        };
+        let lines = self.console_view.paused.as_ref().unwrap_or(&data.console);
+        let filter = self.console_view.filter.to_lowercase();
+        let visible: Vec<&ConsoleLine> = lines
+            .iter()
+            .filter(|line| filter.is_empty() || line.message.to_lowercase().contains(&filter) || line.channel.to_lowercase().contains(&filter))
+            .collect();
+
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let default = ui.visuals().text_color();
+        let weak = ui.visuals().weak_text_color();
+        // Place gardée en bas pour la saisie des commandes
+        let reserved = if self.console_view.completions.is_empty() { 30.0 } else { 55.0 };
+        egui::ScrollArea::both()
+            .id_source("console_scroll")
+            .auto_shrink([false; 2])
+            .max_height(ui.available_height() - reserved)
+            .stick_to_bottom(self.console_view.autoscroll && self.console_view.paused.is_none())
+            .show_rows(ui, row_height, visible.len(), |ui, rows| {
+                if visible.is_empty() {
+                    ui.label(self.translator.t("console_empty"));
+                }
+                for line in &visible[rows] {
+                    ui.add(egui::Label::new(console_job(line, font.clone(), default, weak)).wrap(false));
+                }
+            });
+    }
+
+    /// Saisie d'une commande : ↑/↓ parcourent l'historique, Tab complète les noms de ressources
+    fn render_console_input(&mut self, ui: &mut egui::Ui) {
+        let is_operator = self.watcher_data.lock().map(|data| data.role >= Role::Operator).unwrap_or(false);
+        let id = egui::Id::new("console_input");
+
+        let focused = ui.memory(|memory| memory.has_focus(id));
+        let (tab, up, down) = if focused {
+            ui.input_mut(|input| {
+                (
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                )
+            })
+        } else {
+            (false, false, false)
+        };
+        let mut moved = false;
+        if tab {
+            self.complete_console_input();
+            moved = true;
+        }
+        if up || down {
+            self.browse_console_history(up);
+            moved = true;
+        }
+
+        let mut submit = false;
+        ui.horizontal(|ui| {
+            ui.monospace(">");
+            let input = egui::TextEdit::singleline(&mut self.console_view.input)
+                .id(id)
+                .font(egui::TextStyle::Monospace)
+                .lock_focus(true)
+                .desired_width(ui.available_width() - 80.0)
+                .hint_text(self.translator.t("console_input_hint"));
+            let response = ui.add_enabled(is_operator, input);
+            let response = if is_operator {
+                response
+            } else {
+                response.on_disabled_hover_text(self.translator.t("console_operator_only"))
+            };
+            if response.changed() {
+                self.console_view.history_pos = None;
+                self.console_view.completions.clear();
+            }
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                submit = true;
+                response.request_focus();
+            }
+            if ui.add_enabled(is_operator, egui::Button::new(self.translator.t("console_run"))).clicked() {
+                submit = true;
+            }
+        });
+
+        // Le curseur suit le texte remplacé par l'historique ou la complétion
+        if moved {
+            if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+                let end = egui::text::CCursor::new(self.console_view.input.chars().count());
+                state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+                state.store(ui.ctx(), id);
+            }
+        }
+        if !self.console_view.completions.is_empty() {
+            ui.weak(self.console_view.completions.join("  "));
+        }
+        if submit {
+            self.run_console_command();
+        }
+    }
+
+    fn run_console_command(&mut self) {
+        let command = self.console_view.input.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        let id = self.console_view.next_command_id;
+        self.console_view.next_command_id += 1;
+        if self.console_view.history.last() != Some(&command) {
+            self.console_view.history.push(command.clone());
+        }
+        self.console_view.history_pos = None;
+        self.console_view.completions.clear();
+        self.console_view.input.clear();
+
+        let sent = self.send_to_watcher(&ClientMessage::ExecuteCommand { id, command: command.clone() });
+        if let Ok(mut data) = self.watcher_data.lock() {
+            let message = match sent {
+                true => format!("^5> {}^7", command),
+                false => format!("^1> {}: {}^7", command, self.translator.t("console_not_connected")),
+            };
+            Self::push_console(&mut data, "hot-reload", message);
+        }
+    }
+
+    fn browse_console_history(&mut self, older: bool) {
+        let history = &self.console_view.history;
+        if history.is_empty() {
+            return;
+        }
+        let position = match (self.console_view.history_pos, older) {
+            (None, true) => Some(history.len() - 1),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i + 1 < history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.console_view.input = position.map(|i| history[i].clone()).unwrap_or_default();
+        self.console_view.history_pos = position;
+    }
+
+    /// Complète le dernier mot avec un nom de ressource, jusqu'au préfixe commun s'il y en a plusieurs
+    fn complete_console_input(&mut self) {
+        let input = &self.console_view.input;
+        let start = input.rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let word = &input[start..];
+        if word.is_empty() {
+            return;
+        }
+
+        let mut candidates: Vec<String> = match self.resource_tree.lock() {
+            Ok(tree) => tree.keys().filter(|name| name.starts_with(word)).cloned().collect(),
+            Err(_) => return,
+        };
+        candidates.sort();
+        let Some(first) = candidates.first() else {
+            self.console_view.completions.clear();
+            return;
+        };
+
+        let common = candidates.iter().fold(first.len(), |len, candidate| {
+            first.chars().zip(candidate.chars()).take_while(|(a, b)| a == b).count().min(len)
+        });
+        let mut completed = format!("{}{}", &input[..start], &first[..first.char_indices().nth(common).map(|(i, _)| i).unwrap_or(first.len())]);
+        if candidates.len() == 1 {
+            completed.push(' ');
+            self.console_view.completions.clear();
+        } else {
+            self.console_view.completions = candidates;
+        }
+        self.console_view.input = completed;
+    }
+}
@@ -0,0 +1,246 @@
+use super::HotReloadApp;
+use eframe::egui;
+use hot_reload_common::{Diagnostic, OriginalLocation, ScriptError, Severity};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Diagnostics d'une ressource par fichier, avec le linter qui les a produits
+type FileDiagnostics<'a> = BTreeMap<&'a str, Vec<(&'a str, &'a Diagnostic)>>;
+
+fn severity_style(severity: Severity) -> (&'static str, egui::Color32) {
+    match severity {
+        Severity::Error => ("⛔", egui::Color32::from_rgb(230, 70, 70)),
+        Severity::Warning => ("⚠", egui::Color32::from_rgb(230, 180, 40)),
+        Severity::Info => ("ℹ", egui::Color32::from_rgb(100, 160, 230)),
+    }
+}
+
+impl HotReloadApp {
+    pub fn diagnostics_count(&self) -> usize {
+        match self.watcher_data.lock() {
+            Ok(data) => data.diagnostics.values().map(Vec::len).sum::<usize>() + data.script_errors.len(),
+            Err(_) => 0,
+        }
+    }
+
+    pub fn render_diagnostics(&mut self, ui: &mut egui::Ui) {
+        let (diagnostics, script_errors) = match self.watcher_data.lock() {
+            Ok(data) => (data.diagnostics.clone(), data.script_errors.clone()),
+            Err(_) => return,
+        };
+
+        let count = |severity: Severity| {
+            let runtime = if severity == Severity::Error { script_errors.len() } else { 0 };
+            diagnostics
+                .values()
+                .flatten()
+                .filter(|diagnostic| diagnostic.severity == severity)
+                .count()
+                + runtime
+        };
+
+        ui.horizontal(|ui| {
+            for (severity, key) in [
+                (Severity::Error, "diagnostics_errors"),
+                (Severity::Warning, "diagnostics_warnings"),
+                (Severity::Info, "diagnostics_infos"),
+            ] {
+                let (icon, color) = severity_style(severity);
+                let mut shown = self.diagnostics_view.severities.contains(&severity);
+                let label = egui::RichText::new(format!("{} {} ({})", icon, self.translator.t(key), count(severity))).color(color);
+                if ui.checkbox(&mut shown, label).changed() {
+                    if shown {
+                        self.diagnostics_view.severities.push(severity);
+                    } else {
+                        self.diagnostics_view.severities.retain(|s| *s != severity);
+                    }
+                }
+            }
+            ui.separator();
+            ui.label("🔍");
+            ui.text_edit_singleline(&mut self.diagnostics_view.filter);
+        });
+        ui.separator();
+
+        let filter = self.diagnostics_view.filter.to_lowercase();
+        let mut reveal = None;
+        if self.diagnostics_view.severities.contains(&Severity::Error) {
+            self.render_script_errors(ui, &script_errors, &filter, &mut reveal);
+        }
+
+        // Ressource -> fichier -> diagnostics de tous les linters, triés par position
+        let mut grouped: BTreeMap<&str, FileDiagnostics> = BTreeMap::new();
+        for ((resource, file, source), list) in &diagnostics {
+            if !filter.is_empty() && !format!("{}/{}", resource, file).to_lowercase().contains(&filter) {
+                continue;
+            }
+            for diagnostic in list.iter().filter(|d| self.diagnostics_view.severities.contains(&d.severity)) {
+                grouped
+                    .entry(resource)
+                    .or_default()
+                    .entry(file)
+                    .or_default()
+                    .push((source, diagnostic));
+            }
+        }
+
+        if grouped.is_empty() {
+            if script_errors.is_empty() {
+                ui.label(self.translator.t("diagnostics_empty"));
+            }
+            if let Some((resource, file)) = reveal {
+                self.reveal_in_tree(&resource, file.as_deref());
+            }
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("diagnostics_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (resource, files) in grouped {
+                    let total: usize = files.values().map(Vec::len).sum();
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(format!("{} ({})", resource, total)).color(egui::Color32::from_rgb(255, 208, 0)),
+                    )
+                    .id_source(("diagnostics_resource", resource))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for (file, mut entries) in files {
+                            entries.sort_by_key(|(_, d)| (d.line, d.column, d.severity));
+                            egui::CollapsingHeader::new(format!("{} ({})", file, entries.len()))
+                                .id_source(("diagnostics_file", resource, file))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    for (source, diagnostic) in entries {
+                                        let (icon, color) = severity_style(diagnostic.severity);
+                                        ui.horizontal(|ui| {
+                                            ui.colored_label(color, icon);
+                                            let position = egui::RichText::new(format!("{}:{}", diagnostic.line, diagnostic.column)).monospace();
+                                            if ui.link(position).on_hover_text(self.translator.t("diagnostics_reveal")).clicked() {
+                                                reveal = Some((resource.to_string(), Some(file.to_string())));
+                                            }
+                                            ui.label(&diagnostic.message);
+                                            let code = match &diagnostic.code {
+                                                Some(code) => format!("{} {}", source, code),
+                                                None => source.to_string(),
+                                            };
+                                            ui.weak(code);
+                                        });
+                                    }
+                                });
+                        }
+                    });
+                }
+            });
+
+        if let Some((resource, file)) = reveal {
+            self.reveal_in_tree(&resource, file.as_deref());
+        }
+    }
+
+    /// Position dans le TypeScript d'origine, à côté de celle du JavaScript compilé
+    fn original_link(&self, ui: &mut egui::Ui, original: &OriginalLocation) -> bool {
+        ui.weak("→");
+        let position = format!("{}:{}:{}", original.file, original.line, original.column);
+        ui.link(egui::RichText::new(position).monospace().color(egui::Color32::from_rgb(100, 160, 230)))
+            .on_hover_text(self.translator.t("script_error_original"))
+            .clicked()
+    }
+
+    /// Erreurs d'exécution des scripts, chaque position renvoie au fichier dans l'arbre
+    fn render_script_errors(
+        &mut self,
+        ui: &mut egui::Ui,
+        errors: &VecDeque<ScriptError>,
+        filter: &str,
+        reveal: &mut Option<(String, Option<String>)>,
+    ) {
+        if errors.is_empty() {
+            return;
+        }
+        let (icon, color) = severity_style(Severity::Error);
+
+        let mut clear = false;
+        egui::CollapsingHeader::new(
+            egui::RichText::new(format!("{} {} ({})", icon, self.translator.t("script_errors"), errors.len())).color(color),
+        )
+        .id_source("script_errors")
+        .default_open(true)
+        .show(ui, |ui| {
+            if ui.button(self.translator.t("script_errors_clear")).clicked() {
+                clear = true;
+            }
+
+            for (i, error) in errors.iter().enumerate() {
+                let resource = error.resource.clone().unwrap_or_default();
+                if !filter.is_empty() && !format!("{}/{}", resource, error.file.as_deref().unwrap_or_default()).to_lowercase().contains(filter) {
+                    continue;
+                }
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.colored_label(color, icon);
+                    if !resource.is_empty() {
+                        ui.strong(format!("[{}]", resource));
+                    }
+                    if let Some(file) = &error.file {
+                        let position = match error.line {
+                            Some(line) => format!("{}:{}", file, line),
+                            None => file.clone(),
+                        };
+                        let link = ui.link(egui::RichText::new(position).monospace());
+                        if link.on_hover_text(self.translator.t("diagnostics_reveal")).clicked() && !resource.is_empty() {
+                            *reveal = Some((resource.clone(), Some(file.clone())));
+                        }
+                    }
+                    if let Some(original) = &error.original {
+                        if self.original_link(ui, original) && !resource.is_empty() {
+                            *reveal = Some((resource.clone(), Some(original.file.clone())));
+                        }
+                    }
+                    ui.label(&error.message);
+                });
+
+                if error.frames.is_empty() {
+                    continue;
+                }
+                egui::CollapsingHeader::new(format!("{} ({})", self.translator.t("script_error_stack"), error.frames.len()))
+                    .id_source(("script_error_stack", i, &error.message))
+                    .show(ui, |ui| {
+                        for frame in &error.frames {
+                            ui.horizontal(|ui| {
+                                ui.weak(frame.function.as_deref().unwrap_or("?"));
+                                let position = match frame.line {
+                                    Some(line) => format!("{}:{}", frame.file, line),
+                                    None => frame.file.clone(),
+                                };
+                                // Sans `@ressource/`, le fichier appartient sans doute à la ressource en erreur
+                                let owner = frame.resource.clone().or_else(|| error.resource.clone());
+                                match &owner {
+                                    Some(owner) => {
+                                        if ui.link(egui::RichText::new(position).monospace()).clicked() {
+                                            *reveal = Some((owner.clone(), Some(frame.file.clone())));
+                                        }
+                                    }
+                                    None => {
+                                        ui.monospace(position);
+                                    }
+                                }
+                                if let (Some(original), Some(owner)) = (&frame.original, owner) {
+                                    if self.original_link(ui, original) {
+                                        *reveal = Some((owner, Some(original.file.clone())));
+                                    }
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+        ui.separator();
+
+        if clear {
+            if let Ok(mut data) = self.watcher_data.lock() {
+                data.script_errors.clear();
+            }
+        }
+    }
+}
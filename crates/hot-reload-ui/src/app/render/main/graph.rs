@@ -0,0 +1,253 @@
+use super::HotReloadApp;
+use eframe::egui;
+use egui::{Align2, Color32, FontId, Pos2, Rect, Sense, Shape, Stroke, Vec2};
+use hot_reload_common::ResourceGraph;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const NODE_SIZE: Vec2 = Vec2::new(160.0, 28.0);
+const COLUMN_GAP: f32 = 220.0;
+const ROW_GAP: f32 = 44.0;
+
+const NODE_COLOR: Color32 = Color32::from_rgb(60, 60, 70);
+const SELECTED_COLOR: Color32 = Color32::from_rgb(40, 110, 200);
+const RESTARTED_COLOR: Color32 = Color32::from_rgb(200, 120, 20);
+const MAYBE_COLOR: Color32 = Color32::from_rgb(120, 90, 40);
+const MISSING_COLOR: Color32 = Color32::from_rgb(220, 50, 50);
+const EDGE_COLOR: Color32 = Color32::from_gray(140);
+
+/// Position des ressources : une colonne par niveau de dépendance, de gauche à droite
+fn layout(graph: &ResourceGraph) -> HashMap<String, Pos2> {
+    let mut depth: HashMap<&str, usize> = graph.nodes.iter().map(|node| (node.name.as_str(), 0)).collect();
+
+    // Les cycles empêchent de converger, on s'arrête après autant de passes que de ressources
+    for _ in 0..graph.nodes.len() {
+        let mut changed = false;
+        for node in &graph.nodes {
+            let required = node
+                .dependencies
+                .iter()
+                .chain(&node.imports)
+                .filter_map(|name| depth.get(name.as_str()))
+                .map(|d| d + 1)
+                .max()
+                .unwrap_or(0);
+            if required > depth[node.name.as_str()] {
+                depth.insert(node.name.as_str(), required);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut columns: HashMap<usize, Vec<&str>> = HashMap::new();
+    for (name, d) in &depth {
+        columns.entry(*d).or_default().push(name);
+    }
+
+    let mut positions = HashMap::new();
+    for (d, mut names) in columns {
+        names.sort_by_key(|name| name.to_lowercase());
+        for (row, name) in names.into_iter().enumerate() {
+            positions.insert(name.to_string(), Pos2::new(d as f32 * COLUMN_GAP, row as f32 * ROW_GAP));
+        }
+    }
+    positions
+}
+
+/// Ressources redémarrées par un changement de `selected`, et celles qui ne le
+/// seraient que si le fichier modifié est importé avec `@ressource/fichier`
+fn impact(graph: &ResourceGraph, selected: &str) -> (HashSet<String>, HashSet<String>) {
+    let dependents = |name: &str| -> Vec<String> {
+        graph
+            .nodes
+            .iter()
+            .filter(|node| !node.no_cascade && node.dependencies.iter().any(|d| d == name))
+            .map(|node| node.name.clone())
+            .collect()
+    };
+
+    let mut restarted = HashSet::new();
+    let mut queue = VecDeque::from([selected.to_string()]);
+    while let Some(name) = queue.pop_front() {
+        for dependent in dependents(&name) {
+            if dependent != selected && restarted.insert(dependent.clone()) {
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    let mut maybe = HashSet::new();
+    let mut queue: VecDeque<String> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.imports.iter().any(|i| i == selected))
+        .map(|node| node.name.clone())
+        .collect();
+    while let Some(name) = queue.pop_front() {
+        if name == selected || restarted.contains(&name) || !maybe.insert(name.clone()) {
+            continue;
+        }
+        queue.extend(dependents(&name));
+    }
+
+    (restarted, maybe)
+}
+
+impl HotReloadApp {
+    pub fn render_graph(&mut self, ui: &mut egui::Ui) {
+        let graph = match self.watcher_data.lock() {
+            Ok(data) => data.graph.clone(),
+            Err(_) => None,
+        };
+        let Some(graph) = graph else {
+            ui.label(self.translator.t("graph_empty"));
+            return;
+        };
+
+        if self.graph_view.layout_for.as_ref() != Some(&graph) {
+            self.graph_view.positions = layout(&graph);
+            self.graph_view.layout_for = Some(graph.clone());
+        }
+
+        let missing: Vec<_> = graph.nodes.iter().filter(|node| !node.missing.is_empty()).collect();
+        ui.horizontal(|ui| {
+            if ui.button(self.translator.t("graph_reset_view")).clicked() {
+                self.graph_view.offset = Vec2::new(20.0, 20.0);
+                self.graph_view.zoom = 1.0;
+            }
+            ui.separator();
+            ui.colored_label(SELECTED_COLOR, self.translator.t("graph_selected"));
+            ui.colored_label(RESTARTED_COLOR, self.translator.t("graph_restarted"));
+            ui.colored_label(MAYBE_COLOR, self.translator.t("graph_maybe_restarted"));
+            ui.label(format!("— {}", self.translator.t("graph_dependency_edge")));
+            ui.label(format!("┄ {}", self.translator.t("graph_import_edge")));
+            if !missing.is_empty() {
+                ui.separator();
+                ui.menu_button(
+                    egui::RichText::new(format!("⚠ {} ({})", self.translator.t("graph_missing_dependencies"), missing.len()))
+                        .color(MISSING_COLOR),
+                    |ui| {
+                        for node in &missing {
+                            ui.label(format!("{} → {}", node.name, node.missing.join(", ")));
+                        }
+                    },
+                );
+            }
+        });
+
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+        let view = &mut self.graph_view;
+
+        if response.dragged() {
+            view.offset += response.drag_delta();
+        }
+        if let Some(pointer) = response.hover_pos() {
+            let (zoom_delta, scroll) = ui.input(|i| (i.zoom_delta(), i.smooth_scroll_delta.y));
+            let factor = zoom_delta * (1.0 + scroll * 0.002);
+            if factor != 1.0 {
+                // Zoom autour du curseur : le point sous la souris ne bouge pas
+                let anchor = (pointer - response.rect.min - view.offset) / view.zoom;
+                view.zoom = (view.zoom * factor).clamp(0.2, 3.0);
+                view.offset = pointer - response.rect.min - anchor * view.zoom;
+            }
+        }
+
+        let zoom = view.zoom;
+        let origin = response.rect.min + view.offset;
+        let node_rect = |position: Pos2| Rect::from_min_size(origin + position.to_vec2() * zoom, NODE_SIZE * zoom);
+        let rects: HashMap<&str, Rect> = graph
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node.name.as_str(), node_rect(*view.positions.get(&node.name)?))))
+            .collect();
+
+        let pointer = response.hover_pos();
+        let hovered = pointer.and_then(|pointer| {
+            rects.iter().find(|(_, rect)| rect.contains(pointer)).map(|(name, _)| name.to_string())
+        });
+        if response.clicked() {
+            view.selected = match &hovered {
+                Some(name) if view.selected.as_ref() != Some(name) => Some(name.clone()),
+                _ => None,
+            };
+        }
+
+        let (restarted, maybe) = match &view.selected {
+            Some(selected) => impact(&graph, selected),
+            None => (HashSet::new(), HashSet::new()),
+        };
+
+        let stroke = Stroke::new(1.5 * zoom, EDGE_COLOR);
+        for node in &graph.nodes {
+            let Some(to) = rects.get(node.name.as_str()) else {
+                continue;
+            };
+            for dependency in &node.dependencies {
+                if let Some(from) = rects.get(dependency.as_str()) {
+                    let start = from.right_center();
+                    let end = to.left_center();
+                    painter.arrow(start, end - start, stroke);
+                }
+            }
+            for import in &node.imports {
+                if let Some(from) = rects.get(import.as_str()) {
+                    let points = [from.right_center(), to.left_center()];
+                    painter.extend(Shape::dashed_line(&points, stroke, 6.0 * zoom, 4.0 * zoom));
+                }
+            }
+        }
+
+        for node in &graph.nodes {
+            let Some(rect) = rects.get(node.name.as_str()) else {
+                continue;
+            };
+            let fill = if view.selected.as_ref() == Some(&node.name) {
+                SELECTED_COLOR
+            } else if restarted.contains(&node.name) {
+                RESTARTED_COLOR
+            } else if maybe.contains(&node.name) {
+                MAYBE_COLOR
+            } else {
+                NODE_COLOR
+            };
+            let border = if node.missing.is_empty() {
+                Stroke::new(1.0, Color32::from_gray(90))
+            } else {
+                Stroke::new(2.0 * zoom, MISSING_COLOR)
+            };
+            painter.rect(*rect, 4.0 * zoom, fill, border);
+
+            let label = if node.no_cascade {
+                format!("{} ⏸", node.name)
+            } else {
+                node.name.clone()
+            };
+            painter.text(rect.center(), Align2::CENTER_CENTER, label, FontId::proportional(13.0 * zoom), Color32::WHITE);
+        }
+
+        if let Some(name) = hovered {
+            if let Some(node) = graph.nodes.iter().find(|node| node.name == name) {
+                egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("graph_node_tooltip"), |ui| {
+                    ui.strong(&node.name);
+                    if !node.dependencies.is_empty() {
+                        ui.label(format!("{}: {}", self.translator.t("graph_dependencies"), node.dependencies.join(", ")));
+                    }
+                    if !node.imports.is_empty() {
+                        ui.label(format!("{}: {}", self.translator.t("graph_imports"), node.imports.join(", ")));
+                    }
+                    if !node.missing.is_empty() {
+                        ui.colored_label(
+                            MISSING_COLOR,
+                            format!("{}: {}", self.translator.t("graph_missing_dependencies"), node.missing.join(", ")),
+                        );
+                    }
+                    if node.no_cascade {
+                        ui.label(self.translator.t("graph_no_cascade"));
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::HotReloadApp;
+use crate::app::HistoryRange;
+use chrono::{DateTime, Local};
+use eframe::egui;
+use hot_reload_common::{ClientMessage, HistoryEntry, HistoryEvent, ReloadReason};
+
+const HISTORY_LIMIT: usize = 1000;
+
+fn entry_time(ms: u64) -> String {
+    DateTime::from_timestamp_millis(ms as i64)
+        .map(|time| time.with_timezone(&Local).format("%d/%m %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// Type de la requête suivi de ses champs, `resource_action resources=["a"] action="restart"`
+fn request_label(request: &ClientMessage) -> String {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(request) else {
+        return format!("{:?}", request);
+    };
+    let kind = fields.get("type").and_then(|kind| kind.as_str()).unwrap_or("?").to_string();
+    fields
+        .iter()
+        .filter(|(name, value)| *name != "type" && !value.is_null())
+        .fold(kind, |label, (name, value)| format!("{} {}={}", label, name, value))
+}
+
+impl HotReloadApp {
+    fn query_history(&mut self) {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let filter = |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+        let query = ClientMessage::QueryHistory {
+            from_ms: self.history_view.range.millis().map(|range| now.saturating_sub(range)),
+            to_ms: None,
+            resource: filter(&self.history_view.resource),
+            user: filter(&self.history_view.user),
+            limit: Some(HISTORY_LIMIT),
+        };
+        self.history_view.requested = self.send_to_watcher(&query);
+    }
+
+    fn history_event(&self, ui: &mut egui::Ui, event: &HistoryEvent) {
+        match event {
+            HistoryEvent::Change { change_type, files } => {
+                ui.label(format!("📝 {:?}: {}", change_type, files.join(", ")));
+            }
+            HistoryEvent::Reload { reason, strategy, success, message } => {
+                let (icon, color) = match success {
+                    true => ("✅", egui::Color32::from_rgb(90, 200, 90)),
+                    false => ("❌", egui::Color32::from_rgb(230, 70, 70)),
+                };
+                let reason = match reason {
+                    ReloadReason::FileChanged => self.translator.t("history_reason_file"),
+                    ReloadReason::Dependency { resource } => format!("⛓ {}", resource),
+                    ReloadReason::Import { resource, file } => format!("📎 @{}/{}", resource, file),
+                    ReloadReason::Manual { action } => self.translator.t(&format!("action_{}", action.name())),
+                };
+                let text = format!("{} {} ({}) {}", icon, reason, self.strategy_label(strategy), message.as_deref().unwrap_or_default());
+                ui.label(egui::RichText::new(text).color(color));
+            }
+            HistoryEvent::Command { command, error } => {
+                match error {
+                    Some(error) => ui.label(egui::RichText::new(format!("⌨️ {}: {}", command, error)).color(egui::Color32::from_rgb(230, 70, 70))),
+                    None => ui.label(format!("⌨️ {}", command)),
+                };
+            }
+            HistoryEvent::Request { request, refused } => {
+                let text = format!("🖱️ {}", request_label(request));
+                if *refused {
+                    ui.label(egui::RichText::new(format!("{} ⛔ {}", text, self.translator.t("history_refused"))).color(egui::Color32::from_rgb(230, 70, 70)))
+                } else {
+                    ui.label(text)
+                };
+            }
+        }
+    }
+
+    /// Historique persistant du watcher : changements, rechargements et requêtes des clients
+    pub fn render_history(&mut self, ui: &mut egui::Ui) {
+        if !self.history_view.requested {
+            self.query_history();
+        }
+        let (page, me) = match self.watcher_data.lock() {
+            Ok(data) => (data.history.clone(), data.user.clone()),
+            Err(_) => return,
+        };
+
+        let mut search = false;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("history_range")
+                .selected_text(self.translator.t(self.history_view.range.key()))
+                .show_ui(ui, |ui| {
+                    for range in HistoryRange::ALL {
+                        search |= ui.selectable_value(&mut self.history_view.range, range, self.translator.t(range.key())).changed();
+                    }
+                });
+            ui.label(self.translator.t("history_resource"));
+            let resource = ui.add(egui::TextEdit::singleline(&mut self.history_view.resource).desired_width(120.0));
+            ui.label(self.translator.t("history_user"));
+            let user = ui.add(egui::TextEdit::singleline(&mut self.history_view.user).desired_width(160.0));
+            if !me.is_empty() && ui.small_button(self.translator.t("history_me")).on_hover_text(&me).clicked() {
+                self.history_view.user = me.clone();
+                search = true;
+            }
+            let submitted = (resource.lost_focus() || user.lost_focus()) && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button(format!("🔍 {}", self.translator.t("history_search"))).clicked() || submitted {
+                search = true;
+            }
+        });
+        if search {
+            self.query_history();
+        }
+        ui.separator();
+
+        let Some(page) = page else {
+            ui.weak(self.translator.t("history_loading"));
+            return;
+        };
+        if page.entries.is_empty() {
+            ui.label(self.translator.t("history_empty"));
+            return;
+        }
+        if page.truncated {
+            ui.weak(format!("{} {}", self.translator.t("history_truncated"), page.entries.len()));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("history_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("history_grid").striped(true).num_columns(4).show(ui, |ui| {
+                    for entry in &page.entries {
+                        self.history_row(ui, entry);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    fn history_row(&self, ui: &mut egui::Ui, entry: &HistoryEntry) {
+        ui.monospace(entry_time(entry.timestamp_ms));
+        match &entry.user {
+            Some(user) => ui.label(user),
+            None => ui.weak(self.translator.t("history_watcher")),
+        };
+        ui.strong(entry.resources.join(", "));
+        self.history_event(ui, &entry.event);
+    }
+}
@@ -1,5 +1,6 @@
 use super::HotReloadApp;
 use eframe::egui;
+use hot_reload_common::ScanDiagnostic;
 
 impl HotReloadApp {
     pub fn render_hot(&mut self, ui: &mut egui::Ui) {
@@ -15,5 +16,135 @@ impl HotReloadApp {
                 ));
             });
         }
+
+        self.render_stats(ui);
+        self.render_scan_report(ui);
+        self.render_strategies(ui);
+    }
+
+    fn render_stats(&mut self, ui: &mut egui::Ui) {
+        let stats = match self.watcher_data.lock() {
+            Ok(data) => data.stats.clone(),
+            Err(_) => None,
+        };
+        let Some(stats) = stats else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}: {}", self.translator.t("stats_indexed_files"), stats.indexed_files));
+            ui.separator();
+            ui.label(format!("{}: {}", self.translator.t("stats_processed_events"), stats.processed_events));
+            ui.separator();
+            ui.label(format!("{}: {}", self.translator.t("stats_suppressed_events"), stats.suppressed_events))
+                .on_hover_text(self.translator.t("stats_suppressed_events_hint"));
+            if stats.dropped_events > 0 {
+                ui.separator();
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", self.translator.t("stats_dropped_events"), stats.dropped_events))
+                        .color(egui::Color32::YELLOW),
+                )
+                .on_hover_text(self.translator.t("stats_dropped_events_hint"));
+            }
+        });
+    }
+
+    fn render_scan_report(&mut self, ui: &mut egui::Ui) {
+        let report = match self.watcher_data.lock() {
+            Ok(data) => data.scan_report.clone(),
+            Err(_) => None,
+        };
+        let Some(report) = report else {
+            return;
+        };
+
+        if report.diagnostics.is_empty() {
+            ui.label(
+                egui::RichText::new(self.translator.t("scan_report_clean"))
+                    .color(egui::Color32::GREEN),
+            );
+            return;
+        }
+
+        egui::CollapsingHeader::new(
+            egui::RichText::new(format!(
+                "{} ({})",
+                self.translator.t("scan_report"),
+                report.diagnostics.len()
+            ))
+            .color(egui::Color32::YELLOW),
+        )
+        .id_source("scan_report")
+        .show(ui, |ui| {
+            for diagnostic in &report.diagnostics {
+                match diagnostic {
+                    ScanDiagnostic::DuplicateResource { name, paths } => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}: {}",
+                                self.translator.t("scan_duplicate_resource"),
+                                name
+                            ))
+                            .color(egui::Color32::RED),
+                        );
+                        ui.indent(format!("duplicate_{}", name), |ui| {
+                            for path in paths {
+                                ui.monospace(path);
+                            }
+                        });
+                    }
+                    ScanDiagnostic::NestedResource { name, path, parent } => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}: {} ({})",
+                                self.translator.t("scan_nested_resource"),
+                                name,
+                                parent
+                            ))
+                            .color(egui::Color32::YELLOW),
+                        );
+                        ui.indent(format!("nested_{}", path), |ui| {
+                            ui.monospace(path);
+                        });
+                    }
+                    ScanDiagnostic::NoScripts { name, path } => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}: {}",
+                                self.translator.t("scan_no_scripts"),
+                                name
+                            ))
+                            .color(egui::Color32::YELLOW),
+                        );
+                        ui.indent(format!("no_scripts_{}", path), |ui| {
+                            ui.monospace(path);
+                        });
+                    }
+                    ScanDiagnostic::UnreadableDirectory { path, error } => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}: {}",
+                                self.translator.t("scan_unreadable_directory"),
+                                error
+                            ))
+                            .color(egui::Color32::RED),
+                        );
+                        ui.indent(format!("unreadable_{}", path), |ui| {
+                            ui.monospace(path);
+                        });
+                    }
+                    ScanDiagnostic::DependencyCycle { resources } => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}: {}",
+                                self.translator.t("scan_dependency_cycle"),
+                                resources.join(" ⇄ ")
+                            ))
+                            .color(egui::Color32::RED),
+                        );
+                    }
+                }
+            }
+        });
     }
 }
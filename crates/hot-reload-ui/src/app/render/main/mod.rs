@@ -1,15 +1,52 @@
+mod console;
+mod diagnostics;
+mod graph;
+mod history;
 mod hot;
 mod log;
+mod pending;
+mod strategy;
+mod tests;
 
 use super::HotReloadApp;
+use crate::app::MainTab;
 use eframe::egui;
 
 impl HotReloadApp {
     pub fn render_main(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| { 
-            self.render_hot(ui);
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.main_tab, MainTab::HotReload, self.translator.t("tab_hot_reload"));
+                ui.selectable_value(&mut self.main_tab, MainTab::Graph, self.translator.t("tab_graph"));
+                let issues = self.diagnostics_count();
+                let label = if issues > 0 {
+                    format!("{} ({})", self.translator.t("tab_diagnostics"), issues)
+                } else {
+                    self.translator.t("tab_diagnostics")
+                };
+                ui.selectable_value(&mut self.main_tab, MainTab::Diagnostics, label);
+                ui.selectable_value(&mut self.main_tab, MainTab::Console, self.translator.t("tab_console"));
+                if let Some(count) = self.pending_count() {
+                    let label = format!("{} ({})", self.translator.t("tab_pending"), count);
+                    ui.selectable_value(&mut self.main_tab, MainTab::Pending, label);
+                }
+                ui.selectable_value(&mut self.main_tab, MainTab::History, self.translator.t("tab_history"));
+            });
             ui.separator();
-            self.render_log(ui);
+
+            match self.main_tab {
+                MainTab::HotReload => {
+                    self.render_hot(ui);
+                    ui.separator();
+                    self.render_tests(ui);
+                    self.render_log(ui);
+                }
+                MainTab::Graph => self.render_graph(ui),
+                MainTab::Diagnostics => self.render_diagnostics(ui),
+                MainTab::Console => self.render_console(ui),
+                MainTab::Pending => self.render_pending(ui),
+                MainTab::History => self.render_history(ui),
+            }
         });
     }
 }
@@ -0,0 +1,71 @@
+use super::HotReloadApp;
+use eframe::egui;
+use hot_reload_common::{ApplyMode, ClientMessage, Role};
+
+impl HotReloadApp {
+    /// Nombre de changements en attente, `None` quand l'onglet n'a rien à montrer
+    pub fn pending_count(&self) -> Option<usize> {
+        let data = self.watcher_data.lock().ok()?;
+        let pending = &data.pending;
+        (pending.mode == ApplyMode::Manual || !pending.changes.is_empty()).then_some(pending.changes.len())
+    }
+
+    /// Changements gardés par le watcher en mode manuel, avec ce que leur application relancera
+    pub fn render_pending(&mut self, ui: &mut egui::Ui) {
+        let (pending, is_operator) = match self.watcher_data.lock() {
+            Ok(data) => (data.pending.clone(), data.role >= Role::Operator),
+            Err(_) => return,
+        };
+
+        let mut apply = Vec::new();
+        ui.horizontal(|ui| {
+            let mode = match pending.mode {
+                ApplyMode::Manual => self.translator.t("apply_mode_manual"),
+                ApplyMode::Auto => self.translator.t("apply_mode_auto"),
+            };
+            ui.label(format!("{}: {}", self.translator.t("apply_mode"), mode));
+            ui.separator();
+            let apply_all = egui::Button::new(format!("▶ {} ({})", self.translator.t("pending_apply_all"), pending.changes.len()));
+            if ui.add_enabled(is_operator && !pending.changes.is_empty(), apply_all).clicked() {
+                apply = pending.changes.iter().map(|change| change.resource.clone()).collect();
+            }
+        });
+        ui.separator();
+
+        if pending.changes.is_empty() {
+            ui.label(self.translator.t("pending_empty"));
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("pending_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for change in &pending.changes {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(is_operator, egui::Button::new(self.translator.t("pending_apply"))).clicked() {
+                            apply.push(change.resource.clone());
+                        }
+                        ui.strong(&change.resource);
+                        ui.weak(format!("{:?}", change.change_type));
+                        ui.label(format!("{}: {}", self.translator.t("pending_strategy"), self.strategy_label(&change.strategy)));
+                        if !change.restarts.is_empty() {
+                            ui.label(format!("⛓ {}", change.restarts.join(", ")));
+                        }
+                    });
+                    egui::CollapsingHeader::new(format!("{} ({})", self.translator.t("pending_files"), change.files.len()))
+                        .id_source(("pending_files", &change.resource))
+                        .show(ui, |ui| {
+                            for file in &change.files {
+                                ui.monospace(file);
+                            }
+                        });
+                    ui.separator();
+                }
+            });
+
+        if !apply.is_empty() {
+            self.send_to_watcher(&ClientMessage::ApplyPending { resources: apply });
+        }
+    }
+}
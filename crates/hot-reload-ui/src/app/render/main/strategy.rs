@@ -0,0 +1,110 @@
+use super::HotReloadApp;
+use eframe::egui;
+use hot_reload_common::{ClientMessage, ReloadStrategy, Role};
+use std::mem::discriminant;
+
+impl HotReloadApp {
+    pub(super) fn strategy_label(&self, strategy: &ReloadStrategy) -> String {
+        match strategy {
+            ReloadStrategy::Auto => self.translator.t("strategy_auto"),
+            ReloadStrategy::Ensure => "ensure".to_string(),
+            ReloadStrategy::Restart => "restart".to_string(),
+            ReloadStrategy::StopStart => "stop + start".to_string(),
+            ReloadStrategy::RefreshEnsure => "refresh + ensure".to_string(),
+            ReloadStrategy::Never => self.translator.t("strategy_never"),
+            ReloadStrategy::Custom { .. } => self.translator.t("strategy_custom"),
+        }
+    }
+
+    pub fn render_strategies(&mut self, ui: &mut egui::Ui) {
+        if !self.watcher_connected() {
+            return;
+        }
+
+        let (strategies, is_operator) = match self.watcher_data.lock() {
+            Ok(data) => (data.strategies.clone(), data.role >= Role::Operator),
+            Err(_) => return,
+        };
+        let mut resources: Vec<String> = match self.resource_tree.lock() {
+            Ok(tree) => tree.keys().cloned().collect(),
+            Err(_) => return,
+        };
+        resources.extend(strategies.keys().filter(|name| !resources.contains(name)).cloned().collect::<Vec<_>>());
+        resources.sort_by_key(|name| name.to_lowercase());
+
+        let options = [
+            ReloadStrategy::Auto,
+            ReloadStrategy::Ensure,
+            ReloadStrategy::Restart,
+            ReloadStrategy::StopStart,
+            ReloadStrategy::RefreshEnsure,
+            ReloadStrategy::Never,
+            ReloadStrategy::Custom { commands: vec!["ensure {resource}".to_string()] },
+        ];
+
+        egui::CollapsingHeader::new(format!(
+            "{} ({})",
+            self.translator.t("reload_strategies"),
+            strategies.len()
+        ))
+        .id_source("reload_strategies")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                ui.text_edit_singleline(&mut self.strategy_editor.filter);
+            });
+
+            let filter = self.strategy_editor.filter.to_lowercase();
+            let mut changed = Vec::new();
+
+            egui::ScrollArea::vertical()
+                .id_source("reload_strategies_scroll")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    // Custom strategies run console commands, only operators may change them
+                    ui.set_enabled(is_operator);
+                    for resource in resources.iter().filter(|name| name.to_lowercase().contains(&filter)) {
+                        let current = strategies.get(resource).cloned().unwrap_or_default();
+
+                        ui.horizontal(|ui| {
+                            ui.label(resource);
+                            egui::ComboBox::from_id_source(("reload_strategy", resource))
+                                .selected_text(self.strategy_label(&current))
+                                .show_ui(ui, |ui| {
+                                    for option in &options {
+                                        let selected = discriminant(option) == discriminant(&current);
+                                        if ui.selectable_label(selected, self.strategy_label(option)).clicked() && !selected {
+                                            changed.push((resource.clone(), option.clone()));
+                                        }
+                                    }
+                                });
+
+                            // Commandes séparées par des `;`, `{resource}` est remplacé par le nom
+                            if let ReloadStrategy::Custom { commands } = &current {
+                                let text = self
+                                    .strategy_editor
+                                    .custom_commands
+                                    .entry(resource.clone())
+                                    .or_insert_with(|| commands.join("; "));
+                                ui.add(egui::TextEdit::singleline(text).hint_text("stop {resource}; start {resource}"));
+                                if ui.button(self.translator.t("apply")).clicked() {
+                                    let commands = text
+                                        .split(';')
+                                        .map(str::trim)
+                                        .filter(|command| !command.is_empty())
+                                        .map(String::from)
+                                        .collect();
+                                    changed.push((resource.clone(), ReloadStrategy::Custom { commands }));
+                                }
+                            }
+                        });
+                    }
+                });
+
+            for (resource, strategy) in changed {
+                self.strategy_editor.custom_commands.remove(&resource);
+                self.send_to_watcher(&ClientMessage::SetReloadStrategy { resource, strategy });
+            }
+        });
+    }
+}
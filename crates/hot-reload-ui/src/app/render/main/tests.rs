@@ -0,0 +1,100 @@
+use super::HotReloadApp;
+use eframe::egui;
+use hot_reload_common::{TestReport, TestStatus};
+
+impl HotReloadApp {
+    pub fn render_tests(&mut self, ui: &mut egui::Ui) {
+        let mut reports: Vec<TestReport> = match self.watcher_data.lock() {
+            Ok(data) => data.test_reports.values().cloned().collect(),
+            Err(_) => return,
+        };
+        if reports.is_empty() {
+            return;
+        }
+        // Les échecs d'abord, puis par nom
+        reports.sort_by(|a, b| a.success.cmp(&b.success).then_with(|| a.resource.to_lowercase().cmp(&b.resource.to_lowercase())));
+
+        let failing = reports.iter().filter(|report| !report.success).count();
+        let focus = self.test_view.focus.take();
+        let title = egui::RichText::new(format!("🧪 {} ({}/{})", self.translator.t("tests"), failing, reports.len()));
+
+        let mut header = egui::CollapsingHeader::new(if failing > 0 { title.color(egui::Color32::RED) } else { title })
+            .id_source("test_reports");
+        if focus.is_some() {
+            header = header.open(Some(true));
+        }
+        header.show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_source("test_reports_scroll")
+                .max_height(260.0)
+                .show(ui, |ui| {
+                    for report in &reports {
+                        self.render_test_report(ui, report, focus.as_deref() == Some(report.resource.as_str()));
+                    }
+                });
+        });
+    }
+
+    fn render_test_report(&self, ui: &mut egui::Ui, report: &TestReport, focused: bool) {
+        let (icon, color) = if report.success {
+            ("✅", egui::Color32::GREEN)
+        } else {
+            ("❌", egui::Color32::RED)
+        };
+        let title = egui::RichText::new(format!(
+            "{} {} — {} {}, {} {}, {} {} ({} ms)",
+            icon,
+            report.resource,
+            report.passed,
+            self.translator.t("tests_passed"),
+            report.failed,
+            self.translator.t("tests_failed"),
+            report.skipped,
+            self.translator.t("tests_skipped"),
+            report.duration_ms
+        ))
+        .color(color);
+
+        let mut header = egui::CollapsingHeader::new(title).id_source(("test_report", &report.resource));
+        if focused {
+            header = header.open(Some(true));
+        }
+        let response = header.show(ui, |ui| {
+            if let Some(error) = &report.error {
+                ui.colored_label(egui::Color32::YELLOW, error);
+            }
+
+            let failed: Vec<_> = report.cases.iter().filter(|case| case.status == TestStatus::Failed).collect();
+            if failed.is_empty() && report.error.is_none() {
+                ui.label(self.translator.t("tests_all_passed"));
+            }
+            for case in failed {
+                let name = match &case.suite {
+                    Some(suite) => format!("{} › {}", suite, case.name),
+                    None => case.name.clone(),
+                };
+                ui.colored_label(egui::Color32::RED, format!("✖ {}", name));
+                if let Some(message) = &case.message {
+                    ui.indent(("test_case", &report.resource, &case.name), |ui| {
+                        ui.monospace(message);
+                    });
+                }
+            }
+
+            let skipped: Vec<_> = report.cases.iter().filter(|case| case.status == TestStatus::Skipped).collect();
+            if !skipped.is_empty() {
+                egui::CollapsingHeader::new(format!("{} ({})", self.translator.t("tests_skipped"), skipped.len()))
+                    .id_source(("test_skipped", &report.resource))
+                    .show(ui, |ui| {
+                        for case in skipped {
+                            let reason = case.message.as_deref().unwrap_or_default();
+                            ui.weak(format!("{} {}", case.name, reason));
+                        }
+                    });
+            }
+        });
+        if focused {
+            response.header_response.scroll_to_me(Some(egui::Align::Min));
+        }
+    }
+}
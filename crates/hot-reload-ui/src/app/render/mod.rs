@@ -1,4 +1,5 @@
 mod header;
+mod pause;
 mod strip;
 mod tree;
 mod content;
@@ -11,6 +12,7 @@ impl HotReloadApp {
     pub fn render(&mut self, ctx: &egui::Context) {
         self.render_menu_strip(ctx);
         self.render_header(ctx);
+        self.render_pause(ctx);
         self.render_tree(ctx);
         self.render_content(ctx);
     }
@@ -0,0 +1,99 @@
+use super::HotReloadApp;
+use chrono::{DateTime, Local};
+use eframe::egui;
+use hot_reload_common::{ClientMessage, Role};
+
+fn local_time(ms: u64) -> String {
+    DateTime::from_timestamp_millis(ms as i64)
+        .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+impl HotReloadApp {
+    /// Bandeau affiché pendant une pause des rechargements, et fenêtre pour en lancer une
+    pub fn render_pause(&mut self, ctx: &egui::Context) {
+        let (pause, is_operator) = match self.watcher_data.lock() {
+            Ok(data) => (data.pause.clone(), data.role >= Role::Operator),
+            Err(_) => return,
+        };
+
+        if pause.paused && self.watcher_connected() {
+            let mut resume = None;
+            egui::TopBottomPanel::top("pause_banner").show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let color = egui::Color32::from_rgb(240, 160, 40);
+                    ui.colored_label(color, egui::RichText::new(format!("⏸ {}", self.translator.t("pause_active"))).strong());
+                    if let Some(reason) = &pause.reason {
+                        ui.label(format!("— {}", reason));
+                    }
+                    if let Some(since) = pause.since_ms {
+                        ui.weak(format!("{} {}", self.translator.t("pause_since"), local_time(since)));
+                    }
+                    if let Some(until) = pause.until_ms {
+                        ui.weak(format!("{} {}", self.translator.t("pause_until"), local_time(until)));
+                    }
+                    ui.separator();
+                    ui.label(format!("{} {}", pause.pending.len(), self.translator.t("pause_pending")));
+                    ui.add_enabled_ui(is_operator, |ui| {
+                        if ui.button(format!("▶ {}", self.translator.t("pause_resume_apply"))).clicked() {
+                            resume = Some(true);
+                        }
+                        if ui.button(self.translator.t("pause_resume_discard")).clicked() {
+                            resume = Some(false);
+                        }
+                    });
+                });
+                if !pause.pending.is_empty() {
+                    egui::CollapsingHeader::new(self.translator.t("pause_pending_list"))
+                        .id_source("pause_pending")
+                        .show(ui, |ui| {
+                            for change in &pause.pending {
+                                ui.horizontal(|ui| {
+                                    ui.strong(&change.resource_name);
+                                    ui.weak(format!("{:?}", change.change_type));
+                                    ui.monospace(&change.file_path);
+                                });
+                            }
+                        });
+                }
+            });
+            if let Some(apply) = resume {
+                self.send_to_watcher(&ClientMessage::Resume { apply });
+            }
+        }
+
+        if !self.pause_form.open {
+            return;
+        }
+        let mut open = true;
+        let mut submit = false;
+        egui::Window::new(self.translator.t("pause_title"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(self.translator.t("pause_reason"));
+                    ui.add(egui::TextEdit::singleline(&mut self.pause_form.reason).hint_text(self.translator.t("pause_reason_hint")));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(self.translator.t("pause_duration"));
+                    ui.add(egui::DragValue::new(&mut self.pause_form.minutes).clamp_range(0..=1440).suffix(" min"));
+                    ui.weak(self.translator.t("pause_duration_hint"));
+                });
+                if ui.button(format!("⏸ {}", self.translator.t("pause_start"))).clicked() {
+                    submit = true;
+                }
+            });
+
+        if submit {
+            let reason = self.pause_form.reason.trim();
+            self.send_to_watcher(&ClientMessage::Pause {
+                reason: (!reason.is_empty()).then(|| reason.to_string()),
+                duration_ms: (self.pause_form.minutes > 0).then(|| self.pause_form.minutes as u64 * 60_000),
+            });
+            open = false;
+        }
+        self.pause_form.open = open;
+    }
+}
@@ -2,6 +2,7 @@ use super::HotReloadApp;
 use crate::app::generate_api_key;
 use crate::app::Translator;
 use eframe::egui;
+use hot_reload_common::Role;
 
 impl HotReloadApp {
     pub fn render_menu_strip(&mut self, ctx: &egui::Context) {
@@ -25,6 +26,13 @@ impl HotReloadApp {
                 });
 
                 ui.menu_button(self.translator.t("tools"), |ui| {
+                    // La pause concerne tous les clients, seuls les opérateurs la lancent
+                    let can_pause = self.watcher_connected()
+                        && self.watcher_data.lock().map(|data| data.role >= Role::Operator && !data.pause.paused).unwrap_or(false);
+                    if ui.add_enabled(can_pause, egui::Button::new(self.translator.t("pause_menu"))).clicked() {
+                        self.pause_form.open = true;
+                        ui.close_menu();
+                    }
                     ui.separator();
                     if ui.button(self.translator.t("generate_api_key")).clicked() {
                         let api_key = generate_api_key();
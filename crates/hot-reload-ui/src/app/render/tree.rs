@@ -1,7 +1,36 @@
 use super::HotReloadApp;
+use chrono::{DateTime, Local};
 use eframe::egui::ImageSource;
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use hot_reload_common::{ActionState, ActionStatus, BreakerState, ClientMessage, ReloadReason, ResourceAction, ResourceChange, ResourceState, Role, TestReport};
+use crate::app::{MainTab, TreeFilter};
+
+/// Couleur du nom d'une ressource selon son état, l'or habituel tant qu'il est inconnu
+fn state_color(state: Option<ResourceState>) -> egui::Color32 {
+    match state {
+        None => egui::Color32::from_rgb(255, 208, 0),
+        Some(ResourceState::Started) => egui::Color32::from_rgb(90, 200, 90),
+        Some(ResourceState::Starting | ResourceState::Stopping) => egui::Color32::from_rgb(240, 160, 40),
+        Some(ResourceState::Stopped | ResourceState::Uninitialized) => egui::Color32::from_rgb(150, 150, 150),
+        Some(ResourceState::Missing | ResourceState::Unknown) => egui::Color32::from_rgb(230, 70, 70),
+    }
+}
+
+/// Une ressource absente de la table du serveur n'y est pas chargée
+fn state_of(states: &HashMap<String, ResourceState>, resource: &str) -> Option<ResourceState> {
+    if states.is_empty() {
+        return None;
+    }
+    Some(states.get(resource).copied().unwrap_or(ResourceState::Missing))
+}
+
+/// Date d'un instantané, ils survivent aux redémarrages du watcher
+fn snapshot_time(ms: u64) -> String {
+    DateTime::from_timestamp_millis(ms as i64)
+        .map(|time| time.with_timezone(&Local).format("%d/%m %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
 
 impl HotReloadApp {
     fn get_file_icon(&self, file: &str) -> &ImageSource<'static> {
@@ -39,6 +68,25 @@ impl HotReloadApp {
                         self.debug_dump_resources();
                     }
                 });
+                ui.horizontal(|ui| {
+                    let filters = [
+                        (TreeFilter::All, "tree_filter_all"),
+                        (TreeFilter::NotLoaded, "tree_filter_not_loaded"),
+                        (TreeFilter::LoadedWithoutFiles, "tree_filter_loaded_without_files"),
+                    ];
+                    let selected = filters
+                        .iter()
+                        .find(|(filter, _)| *filter == self.tree_state.filter)
+                        .map(|(_, key)| self.translator.t(key))
+                        .unwrap_or_default();
+                    egui::ComboBox::from_id_source("tree_filter")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (filter, key) in filters {
+                                ui.selectable_value(&mut self.tree_state.filter, filter, self.translator.t(key));
+                            }
+                        });
+                });
                 egui::ScrollArea::vertical()
                     .stick_to_right(true)
                     .auto_shrink([false; 2])
@@ -49,7 +97,35 @@ impl HotReloadApp {
                             HashMap::new()
                         };
 
-                        let mut resources: Vec<_> = resources_data.into_iter().collect();
+                        let (last_reloads, test_reports, actions, is_operator, states, breakers) = match self.watcher_data.lock() {
+                            Ok(data) => (
+                                data.last_reloads.clone(),
+                                data.test_reports.clone(),
+                                data.actions.clone(),
+                                data.role >= Role::Operator,
+                                data.resource_states.clone(),
+                                data.breakers.clone(),
+                            ),
+                            Err(_) => (HashMap::new(), HashMap::new(), HashMap::new(), false, HashMap::new(), HashMap::new()),
+                        };
+
+                        let filter = self.tree_state.filter;
+                        if filter != TreeFilter::All && states.is_empty() {
+                            ui.weak(self.translator.t("tree_states_unknown"));
+                            return;
+                        }
+                        if filter == TreeFilter::LoadedWithoutFiles {
+                            let on_disk: HashSet<&String> = resources_data.keys().collect();
+                            self.render_resources_without_files(ui, &states, &on_disk, is_operator);
+                            return;
+                        }
+
+                        let mut resources: Vec<_> = resources_data
+                            .into_iter()
+                            .filter(|(name, _)| {
+                                filter == TreeFilter::All || !state_of(&states, name).is_some_and(ResourceState::is_loaded)
+                            })
+                            .collect();
                         resources.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
 
                         for (resource_name, files) in resources {
@@ -76,14 +152,34 @@ impl HotReloadApp {
                                         .expanded
                                         .insert(resource_name.clone(), is_expanded);
                                 }
-                                ui.checkbox(
+                                let state = state_of(&states, &resource_name);
+                                let response = ui.checkbox(
                                     &mut is_checked,
                                     egui::RichText::new(&resource_name)
-                                        .color(egui::Color32::from_rgb(255, 208, 0)),
+                                        .color(state_color(state)),
                                 );
+                                self.highlight(ui, &response, &resource_name);
+                                let response = match state {
+                                    Some(state) => response.on_hover_text(self.state_label(state)),
+                                    None => response,
+                                };
+                                response.context_menu(|ui| self.resource_menu(ui, &resource_name, is_operator));
                                 self.tree_state
                                     .checked
                                     .insert(resource_name.clone(), is_checked);
+
+                                if let Some(change) = last_reloads.get(&resource_name) {
+                                    ui.label("🔁").on_hover_text(self.reload_reason(change));
+                                }
+                                if let Some(report) = test_reports.get(&resource_name) {
+                                    self.render_test_badge(ui, report);
+                                }
+                                if let Some(status) = actions.get(&resource_name) {
+                                    self.render_action_status(ui, status);
+                                }
+                                if let Some(breaker) = breakers.get(&resource_name).filter(|breaker| breaker.tripped) {
+                                    self.render_breaker_badge(ui, breaker);
+                                }
                             });
 
                             if is_expanded {
@@ -100,11 +196,12 @@ impl HotReloadApp {
                                             let icon = self.get_file_icon(&file);
                                             ui.image(icon.clone());
 
-                                            ui.checkbox(
+                                            let response = ui.checkbox(
                                                 &mut is_file_checked,
                                                 egui::RichText::new(&file)
                                                     .color(egui::Color32::from_rgb(255, 208, 0)),
                                             );
+                                            self.highlight(ui, &response, &file_id);
 
                                             self.tree_state
                                                 .checked
@@ -117,4 +214,257 @@ impl HotReloadApp {
                     });
             });
     }
+
+    /// Encadre l'entrée ouverte depuis un diagnostic et la fait défiler une fois
+    fn highlight(&mut self, ui: &egui::Ui, response: &egui::Response, id: &str) {
+        if self.tree_state.highlighted.as_deref() != Some(id) {
+            return;
+        }
+        ui.painter().rect_stroke(
+            response.rect.expand(2.0),
+            2.0,
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(40, 110, 200)),
+        );
+        if self.tree_state.scroll_to_highlight {
+            response.scroll_to_me(Some(egui::Align::Center));
+            self.tree_state.scroll_to_highlight = false;
+        }
+    }
+
+    /// Déplie la ressource dans l'arbre et y met en évidence le fichier. Les
+    /// chemins absolus (C#, source maps) sont rapprochés par la fin du chemin.
+    pub fn reveal_in_tree(&mut self, resource: &str, file: Option<&str>) {
+        let files = match self.resource_tree.lock() {
+            Ok(tree) => tree.get(resource).cloned(),
+            Err(_) => None,
+        };
+        let Some(files) = files else {
+            return;
+        };
+
+        let target = file.and_then(|file| {
+            let file = file.replace('\\', "/");
+            files.iter().find(|f| **f == file).or_else(|| {
+                files
+                    .iter()
+                    .find(|f| file.ends_with(&format!("/{}", f)) || f.ends_with(&format!("/{}", file)))
+            })
+        });
+        self.tree_state.filter = TreeFilter::All;
+        self.tree_state.expanded.insert(resource.to_string(), true);
+        self.tree_state.highlighted = Some(match target {
+            Some(file) => format!("{}/{}", resource, file),
+            None => resource.to_string(),
+        });
+        self.tree_state.scroll_to_highlight = true;
+    }
+
+    /// Pastille ✅/❌ des derniers tests, un clic ouvre le détail dans le panneau des tests
+    fn render_test_badge(&mut self, ui: &mut egui::Ui, report: &TestReport) {
+        let (text, color) = if report.success {
+            (format!("✅ {}", report.passed), egui::Color32::GREEN)
+        } else {
+            (format!("❌ {}", report.failed), egui::Color32::RED)
+        };
+        let hint = format!(
+            "{}: {} / {}: {} / {}: {}",
+            self.translator.t("tests_passed"),
+            report.passed,
+            self.translator.t("tests_failed"),
+            report.failed,
+            self.translator.t("tests_skipped"),
+            report.skipped
+        );
+        if ui
+            .add(egui::Label::new(egui::RichText::new(text).color(color)).sense(egui::Sense::click()))
+            .on_hover_text(hint)
+            .clicked()
+        {
+            self.main_tab = MainTab::HotReload;
+            self.test_view.focus = Some(report.resource.clone());
+        }
+    }
+
+    fn state_label(&self, state: ResourceState) -> String {
+        let key = match state {
+            ResourceState::Started => "state_started",
+            ResourceState::Starting => "state_starting",
+            ResourceState::Stopped => "state_stopped",
+            ResourceState::Stopping => "state_stopping",
+            ResourceState::Uninitialized => "state_uninitialized",
+            ResourceState::Missing => "state_missing",
+            ResourceState::Unknown => "state_unknown",
+        };
+        format!("{}: {}", self.translator.t("state"), self.translator.t(key))
+    }
+
+    /// Ressources que le serveur fait tourner sans qu'aucun fichier surveillé ne soit sur le disque
+    fn render_resources_without_files(
+        &mut self,
+        ui: &mut egui::Ui,
+        states: &HashMap<String, ResourceState>,
+        on_disk: &HashSet<&String>,
+        is_operator: bool,
+    ) {
+        let mut resources: Vec<(&String, ResourceState)> = states
+            .iter()
+            .filter(|(name, state)| state.is_loaded() && !on_disk.contains(name))
+            .map(|(name, state)| (name, *state))
+            .collect();
+        resources.sort_by_key(|(name, _)| name.to_lowercase());
+        if resources.is_empty() {
+            ui.weak(self.translator.t("tree_filter_empty"));
+        }
+
+        for (name, state) in resources {
+            ui.horizontal(|ui| {
+                ui.label("⚠").on_hover_text(self.translator.t("tree_no_files"));
+                let response = ui
+                    .add(egui::Label::new(egui::RichText::new(name).color(state_color(Some(state)))).sense(egui::Sense::click()))
+                    .on_hover_text(self.state_label(state));
+                response.context_menu(|ui| self.resource_menu(ui, name, is_operator));
+            });
+        }
+    }
+
+    /// Menu du clic droit sur une ressource, les actions passent par le watcher
+    fn resource_menu(&mut self, ui: &mut egui::Ui, resource: &str, is_operator: bool) {
+        if !is_operator {
+            ui.weak(self.translator.t("console_operator_only"));
+        }
+        for action in [
+            ResourceAction::Start,
+            ResourceAction::Stop,
+            ResourceAction::Restart,
+            ResourceAction::RefreshEnsure,
+        ] {
+            let label = self.translator.t(&format!("action_{}", action.name()));
+            if ui.add_enabled(is_operator, egui::Button::new(label)).clicked() {
+                self.send_to_watcher(&ClientMessage::ResourceAction {
+                    resources: vec![resource.to_string()],
+                    action,
+                });
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+
+        let tripped = self
+            .watcher_data
+            .lock()
+            .is_ok_and(|data| data.breakers.get(resource).is_some_and(|breaker| breaker.tripped));
+        if tripped {
+            if ui.add_enabled(is_operator, egui::Button::new(self.translator.t("breaker_reset"))).clicked() {
+                self.send_to_watcher(&ClientMessage::ResetBreaker { resource: resource.to_string() });
+                ui.close_menu();
+            }
+            ui.separator();
+        }
+
+        let snapshots = self
+            .watcher_data
+            .lock()
+            .map(|data| data.snapshots.get(resource).cloned().unwrap_or_default())
+            .unwrap_or_default();
+        ui.add_enabled_ui(is_operator && !snapshots.is_empty(), |ui| {
+            ui.menu_button(self.translator.t("snapshot_menu"), |ui| {
+                for snapshot in &snapshots {
+                    let mut label = format!(
+                        "{} — {} {}",
+                        snapshot_time(snapshot.created_ms),
+                        snapshot.files,
+                        self.translator.t("snapshot_files")
+                    );
+                    if snapshot.before_rollback {
+                        label = format!("{} ({})", label, self.translator.t("snapshot_before_rollback"));
+                    }
+                    if ui.button(label).clicked() {
+                        self.send_to_watcher(&ClientMessage::Rollback {
+                            resource: resource.to_string(),
+                            snapshot: Some(snapshot.id),
+                        });
+                        ui.close_menu();
+                    }
+                }
+            });
+        })
+        .response
+        .on_disabled_hover_text(self.translator.t("snapshot_none"));
+        ui.separator();
+
+        let checked = self.checked_resources();
+        let label = format!("{} ({})", self.translator.t("action_reload_checked"), checked.len());
+        if ui.add_enabled(is_operator && !checked.is_empty(), egui::Button::new(label)).clicked() {
+            self.send_to_watcher(&ClientMessage::ResourceAction {
+                resources: checked,
+                action: ResourceAction::Reload,
+            });
+            ui.close_menu();
+        }
+    }
+
+    /// Ressources cochées dans l'arbre, cochées par défaut comme à l'affichage
+    fn checked_resources(&self) -> Vec<String> {
+        let Ok(tree) = self.resource_tree.lock() else {
+            return Vec::new();
+        };
+        let mut resources: Vec<String> = tree
+            .keys()
+            .filter(|name| self.tree_state.checked.get(*name).copied().unwrap_or(true))
+            .cloned()
+            .collect();
+        resources.sort();
+        resources
+    }
+
+    /// Avancement de la dernière action manuelle, la réponse de FXServer en infobulle
+    fn render_action_status(&self, ui: &mut egui::Ui, status: &ActionStatus) {
+        let action = self.translator.t(&format!("action_{}", status.action.name()));
+        let hint = match &status.message {
+            Some(message) => format!("{}: {}", action, message),
+            None => action,
+        };
+        match status.state {
+            ActionState::Queued => ui.label("⏳"),
+            ActionState::Running => ui.spinner(),
+            ActionState::Succeeded => ui.colored_label(egui::Color32::GREEN, "✔"),
+            ActionState::Failed => ui.colored_label(egui::Color32::RED, "✖"),
+        }
+        .on_hover_text(hint);
+    }
+
+    /// Rechargement automatique coupé après trop d'échecs, la bulle donne la dernière erreur
+    fn render_breaker_badge(&self, ui: &mut egui::Ui, breaker: &BreakerState) {
+        let mut hint = format!("{} ({} {})", self.translator.t("breaker_tripped"), breaker.failures, self.translator.t("breaker_failures"));
+        if let Some(error) = &breaker.last_error {
+            hint.push_str(&format!("\n{}", error));
+        }
+        ui.colored_label(egui::Color32::RED, "⛔").on_hover_text(hint);
+    }
+
+    fn reload_reason(&self, change: &ResourceChange) -> String {
+        match &change.reason {
+            ReloadReason::FileChanged => format!(
+                "{}: {}",
+                self.translator.t("reload_reason_file"),
+                change.file_path
+            ),
+            ReloadReason::Dependency { resource } => format!(
+                "{}: {}",
+                self.translator.t("reload_reason_dependency"),
+                resource
+            ),
+            ReloadReason::Import { resource, file } => format!(
+                "{}: @{}/{}",
+                self.translator.t("reload_reason_import"),
+                resource,
+                file
+            ),
+            ReloadReason::Manual { action } => format!(
+                "{}: {}",
+                self.translator.t("reload_reason_manual"),
+                self.translator.t(&format!("action_{}", action.name()))
+            ),
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use crate::config::{WatchMode, WatcherConfig};
+use notify::{Config, EventHandler, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const PROBE_FILE: &str = ".hot-reload-probe";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Creates the notify backend matching the (resolved) watch mode.
+pub fn create_watcher<F: EventHandler>(
+    config: &WatcherConfig,
+    handler: F,
+) -> Result<Box<dyn Watcher + Send>, BoxError> {
+    match config.watch_mode {
+        WatchMode::Poll => {
+            let poll_config = Config::default()
+                .with_poll_interval(Duration::from_millis(config.poll_interval_ms))
+                .with_compare_contents(true);
+            Ok(Box::new(PollWatcher::new(handler, poll_config)?))
+        }
+        WatchMode::Native | WatchMode::Auto => {
+            Ok(Box::new(RecommendedWatcher::new(handler, Config::default())?))
+        }
+    }
+}
+
+/// Writes a probe file in `root` and checks that the native backend reports it.
+///
+/// Bind mounts from a Windows host and most network shares never deliver
+/// inotify events, in which case the native watcher silently sees nothing.
+pub fn native_events_fire(root: &Path) -> bool {
+    let (tx, rx) = mpsc::channel();
+    let probe = root.join(PROBE_FILE);
+
+    let mut watcher = match RecommendedWatcher::new(tx, Config::default()) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("⚠️ Native watcher unavailable: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = watcher.watch(root, RecursiveMode::NonRecursive) {
+        warn!("⚠️ Native watcher cannot watch {}: {}", root.display(), e);
+        return false;
+    }
+    if let Err(e) = std::fs::write(&probe, b"probe") {
+        warn!("⚠️ Cannot write probe file {}: {}", probe.display(), e);
+        return false;
+    }
+
+    let deadline = std::time::Instant::now() + PROBE_TIMEOUT;
+    let mut fired = false;
+    while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+        match rx.recv_timeout(remaining) {
+            Ok(Ok(event)) if event.paths.iter().any(|path| path.ends_with(PROBE_FILE)) => {
+                fired = true;
+                break;
+            }
+            Ok(_) => continue,
+            Err(_) => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&probe);
+    fired
+}
+
+/// Turns `Auto` into a concrete mode and warns when native events do not fire.
+pub async fn resolve_watch_mode(config: &WatcherConfig) -> WatchMode {
+    if config.watch_mode == WatchMode::Poll {
+        info!("🔁 Polling watcher every {} ms", config.poll_interval_ms);
+        return WatchMode::Poll;
+    }
+
+    let root = config.watch_root();
+    let fired = tokio::task::spawn_blocking(move || native_events_fire(&root))
+        .await
+        .unwrap_or(false);
+
+    match (config.watch_mode, fired) {
+        (_, true) => {
+            info!("✅ Native file events are working");
+            WatchMode::Native
+        }
+        (WatchMode::Auto, false) => {
+            warn!("⚠️ Native file events did not fire, falling back to polling every {} ms", config.poll_interval_ms);
+            WatchMode::Poll
+        }
+        _ => {
+            warn!("⚠️ Native file events did not fire, changes may go unnoticed (set \"watch_mode\": \"poll\" for network shares and bind mounts)");
+            WatchMode::Native
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config::CircuitBreaker;
+use crate::pipeline::merge_change;
+use hot_reload_common::{BreakerState, ResourceChange};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+#[derive(Default)]
+struct Entry {
+    /// When the reloads failed, older than the window are dropped
+    failures: VecDeque<u64>,
+    last_error: Option<String>,
+    tripped_at: Option<u64>,
+    /// Last automatic reload, script errors shortly after it count as its failure
+    reloaded_at: Option<u64>,
+    /// Whether the last reload already counted as failed
+    counted: bool,
+    /// Last failure, even with the breaker disabled, snapshots are only taken without one
+    failed_at: Option<u64>,
+    /// Changes that came in while tripped, reloaded once the breaker is reset
+    blocked: BTreeMap<String, ResourceChange>,
+    /// Files linted since the breaker tripped, only their diagnostics can close it
+    linted: BTreeSet<String>,
+}
+
+/// Circuit breakers of every resource that failed to reload at least once.
+pub struct Breakers {
+    config: CircuitBreaker,
+    entries: HashMap<String, Entry>,
+}
+
+impl Breakers {
+    pub fn new(config: CircuitBreaker) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn state(resource: &str, entry: &Entry) -> BreakerState {
+        BreakerState {
+            resource: resource.to_string(),
+            tripped: entry.tripped_at.is_some(),
+            failures: entry.failures.len(),
+            last_error: entry.last_error.clone(),
+            tripped_at_ms: entry.tripped_at,
+        }
+    }
+
+    pub fn states(&self) -> Vec<BreakerState> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.failed_at.is_some())
+            .map(|(resource, entry)| Self::state(resource, entry))
+            .collect()
+    }
+
+    /// State of the breaker of one resource, closed without failures when it never failed.
+    pub fn state_of(&self, resource: &str) -> BreakerState {
+        match self.entries.get(resource) {
+            Some(entry) => Self::state(resource, entry),
+            None => Self::state(resource, &Entry::default()),
+        }
+    }
+
+    pub fn is_tripped(&self, resource: &str) -> bool {
+        self.entries.get(resource).is_some_and(|entry| entry.tripped_at.is_some())
+    }
+
+    /// Whether the resource failed since `at`, e.g. right after a reload that FXServer accepted.
+    pub fn failed_since(&self, resource: &str, at: u64) -> bool {
+        self.entries.get(resource).and_then(|entry| entry.failed_at).is_some_and(|failed_at| failed_at >= at)
+    }
+
+    pub fn reloaded(&mut self, resource: &str, now: u64) {
+        let entry = self.entries.entry(resource.to_string()).or_default();
+        entry.reloaded_at = Some(now);
+        entry.counted = false;
+    }
+
+    /// Counts a failed reload, returns the new state and whether this failure tripped the breaker.
+    /// Failures are counted with the breaker disabled too, it just never trips.
+    pub fn failed(&mut self, resource: &str, error: String, now: u64) -> (BreakerState, bool) {
+        let window = self.config.window_ms;
+        let threshold = self.config.failures;
+        let entry = self.entries.entry(resource.to_string()).or_default();
+        entry.counted = true;
+        entry.failed_at = Some(now);
+        entry.failures.push_back(now);
+        while entry.failures.front().is_some_and(|at| now.saturating_sub(*at) > window) {
+            entry.failures.pop_front();
+        }
+        entry.last_error = Some(error);
+        let trips = threshold > 0 && entry.tripped_at.is_none() && entry.failures.len() >= threshold;
+        if trips {
+            entry.tripped_at = Some(now);
+            entry.linted.clear();
+        }
+        (Self::state(resource, entry), trips)
+    }
+
+    /// A script error of the resource fails its last reload when it comes within the grace period.
+    pub fn script_error(&mut self, resource: &str, message: &str, now: u64) -> Option<(BreakerState, bool)> {
+        let grace = self.config.error_grace_ms;
+        let entry = self.entries.get(resource)?;
+        let after_reload = entry.reloaded_at.is_some_and(|at| now.saturating_sub(at) <= grace);
+        if entry.counted || !after_reload {
+            return None;
+        }
+        Some(self.failed(resource, message.to_string(), now))
+    }
+
+    pub fn block(&mut self, change: ResourceChange) {
+        if let Some(entry) = self.entries.get_mut(&change.resource_name) {
+            merge_change(&mut entry.blocked, change);
+        }
+    }
+
+    /// Records a file linted while tripped and hands back every file linted since the trip.
+    pub fn linted(&mut self, resource: &str, file: &str) -> Option<BTreeSet<String>> {
+        let entry = self.entries.get_mut(resource).filter(|entry| entry.tripped_at.is_some())?;
+        entry.linted.insert(file.to_string());
+        Some(entry.linted.clone())
+    }
+
+    /// Lets the resource reload again and hands back what was blocked meanwhile.
+    /// After a fix the failures stay counted, one more failure in the window trips it again.
+    pub fn reset(&mut self, resource: &str, forget_failures: bool) -> Option<(BreakerState, BTreeMap<String, ResourceChange>)> {
+        let entry = self.entries.get_mut(resource)?;
+        if entry.tripped_at.is_none() && !forget_failures {
+            return None;
+        }
+        entry.tripped_at = None;
+        entry.linted.clear();
+        if forget_failures {
+            entry.failures.clear();
+        }
+        let blocked = std::mem::take(&mut entry.blocked);
+        Some((Self::state(resource, entry), blocked))
+    }
+}
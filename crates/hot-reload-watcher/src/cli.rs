@@ -0,0 +1,229 @@
+use crate::config::WatcherConfig;
+use clap::{Parser, Subcommand};
+use futures::stream::SplitStream;
+use futures::{SinkExt, StreamExt};
+use hot_reload_common::{ActionState, AuthRequest, ClientMessage, PauseState, ResourceAction, Role, SnapshotList, WatcherMessage};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// How long the running watcher gets to answer
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+/// A rollback waits for the reload, FXServer itself gets 10s to answer
+const ROLLBACK_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Parser)]
+#[command(name = "hot-reload-watcher", about = "Watches FiveM resources and reloads them when they change")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Watches the resources, what runs without a subcommand
+    Run,
+    /// Holds automatic reloads back on the running watcher
+    Pause {
+        #[arg(long)]
+        reason: Option<String>,
+        /// Resumes on its own after that long, e.g. `90s`, `30m` or `2h`
+        #[arg(long = "for", value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// Lets reloads through again on the running watcher
+    Resume {
+        /// Drops the changes held during the pause instead of reloading them
+        #[arg(long)]
+        discard: bool,
+    },
+    /// Shows whether reloads are paused and what is held back
+    Status,
+    /// Lists the snapshots kept for a resource
+    Snapshots { resource: String },
+    /// Restores the files of a snapshot and reloads the resource
+    Rollback {
+        resource: String,
+        /// Id of the snapshot, as listed by `snapshots`, the latest by default
+        #[arg(long = "to")]
+        snapshot: Option<u64>,
+    },
+}
+
+/// `90s`, `30m`, `2h`, or seconds without a unit.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let number: u64 = number.parse().map_err(|_| format!("invalid duration '{}'", text))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => return Err(format!("unknown unit '{}', use s, m or h", unit)),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "0.0.0.0" | "::1" | "[::1]") || host.starts_with("127.")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+fn print_pause(pause: &PauseState) {
+    if !pause.paused {
+        println!("Auto-reload is running");
+        return;
+    }
+    let now = now_ms();
+    println!("Auto-reload is paused: {}", pause.reason.as_deref().unwrap_or("no reason given"));
+    if let Some(since) = pause.since_ms {
+        println!("  paused for {}s", now.saturating_sub(since) / 1000);
+    }
+    if let Some(until) = pause.until_ms {
+        println!("  resumes in {}s", until.saturating_sub(now) / 1000);
+    }
+    println!("  {} pending change(s)", pause.pending.len());
+    for change in &pause.pending {
+        println!("    {} {:?} {}", change.resource_name, change.change_type, change.file_path);
+    }
+}
+
+fn print_snapshots(resource: &str, lists: &[SnapshotList]) {
+    let Some(list) = lists.iter().find(|list| list.resource == resource) else {
+        println!("No snapshot of {}", resource);
+        return;
+    };
+    let now = now_ms();
+    println!("Snapshots of {}, newest first:", resource);
+    for snapshot in &list.snapshots {
+        println!(
+            "  {}  {}s ago  {} file(s)  {} bytes{}",
+            snapshot.id,
+            now.saturating_sub(snapshot.created_ms) / 1000,
+            snapshot.files,
+            snapshot.bytes,
+            if snapshot.before_rollback { "  (before a rollback)" } else { "" }
+        );
+    }
+}
+
+/// Reads messages until `pick` returns something, or fails after `timeout` without one.
+async fn wait_for<T>(
+    ws_read: &mut SplitStream<WsStream>,
+    operator: bool,
+    timeout: Duration,
+    mut pick: impl FnMut(WatcherMessage) -> Option<Result<T, BoxError>>,
+) -> Result<T, BoxError> {
+    loop {
+        let message = tokio::time::timeout(timeout, ws_read.next())
+            .await
+            .map_err(|_| "the watcher did not answer in time")?
+            .ok_or("the watcher closed the connection")??;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        match serde_json::from_str::<WatcherMessage>(&text) {
+            Ok(WatcherMessage::Session(session)) if operator && session.role < Role::Operator => {
+                return Err("the api key does not grant the operator role".into());
+            }
+            Ok(message) => {
+                if let Some(picked) = pick(message) {
+                    return picked;
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Waits for the pause state, sent at connect and after every change.
+async fn next_pause(ws_read: &mut SplitStream<WsStream>, operator: bool) -> Result<PauseState, BoxError> {
+    wait_for(ws_read, operator, ANSWER_TIMEOUT, |message| match message {
+        WatcherMessage::Pause(pause) => Some(Ok(pause)),
+        _ => None,
+    })
+    .await
+}
+
+/// Waits for the reload that follows the rollback of the resource.
+async fn rollback_done(ws_read: &mut SplitStream<WsStream>, resource: &str) -> Result<String, BoxError> {
+    wait_for(ws_read, true, ROLLBACK_TIMEOUT, |message| match message {
+        WatcherMessage::ActionStatus(status) if status.resource == resource && status.action == ResourceAction::Rollback => match status.state {
+            ActionState::Succeeded => Some(Ok(status.message.unwrap_or_default())),
+            ActionState::Failed => Some(Err(status.message.unwrap_or_else(|| "rollback failed".to_string()).into())),
+            ActionState::Queued | ActionState::Running => None,
+        },
+        _ => None,
+    })
+    .await
+}
+
+/// Runs a subcommand against the watcher listening on `config.ws_url()`.
+pub async fn control(config: &WatcherConfig, command: Command) -> Result<(), BoxError> {
+    let (ws_stream, _) = connect_async(config.ws_url()).await.map_err(|e| format!("no watcher running on {}: {}", config.ws_url(), e))?;
+    let (mut ws_write, mut ws_read) = ws_stream.split();
+    // Remote connections start with the api key, local ones are operators right away
+    if !is_loopback(&config.ws_host) {
+        let auth = AuthRequest { api_key: config.api_key.clone() };
+        ws_write.send(Message::Text(serde_json::to_string(&auth)?)).await?;
+    }
+    if let Ok(user) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+        let hello = ClientMessage::Hello { user: format!("{} cli", user) };
+        ws_write.send(Message::Text(serde_json::to_string(&hello)?)).await?;
+    }
+
+    let operator = !matches!(command, Command::Status | Command::Snapshots { .. });
+    // Snapshot lists come at connect, right before the pause state
+    let mut snapshots = Vec::new();
+    let pause = wait_for(&mut ws_read, operator, ANSWER_TIMEOUT, |message| match message {
+        WatcherMessage::Snapshots(list) => {
+            snapshots.push(list);
+            None
+        }
+        WatcherMessage::Pause(pause) => Some(Ok(pause)),
+        _ => None,
+    })
+    .await?;
+
+    match &command {
+        Command::Snapshots { resource } => {
+            print_snapshots(resource, &snapshots);
+            let _ = ws_write.close().await;
+            return Ok(());
+        }
+        Command::Rollback { resource, snapshot } => {
+            let request = ClientMessage::Rollback { resource: resource.clone(), snapshot: *snapshot };
+            ws_write.send(Message::Text(serde_json::to_string(&request)?)).await?;
+            let result = rollback_done(&mut ws_read, resource).await;
+            let _ = ws_write.close().await;
+            println!("{} rolled back: {}", resource, result?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let request = match &command {
+        Command::Pause { reason, duration } => Some(ClientMessage::Pause {
+            reason: reason.clone(),
+            duration_ms: duration.map(|duration| duration.as_millis() as u64),
+        }),
+        Command::Resume { discard } => Some(ClientMessage::Resume { apply: !discard }),
+        Command::Status | Command::Run | Command::Snapshots { .. } | Command::Rollback { .. } => None,
+    };
+    let pause = match request {
+        Some(request) => {
+            ws_write.send(Message::Text(serde_json::to_string(&request)?)).await?;
+            next_pause(&mut ws_read, operator).await?
+        }
+        None => pause,
+    };
+    print_pause(&pause);
+    let _ = ws_write.close().await;
+    Ok(())
+}
@@ -1,12 +1,208 @@
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use hot_reload_common::{ApplyMode, ReloadStrategy, Role};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// inotify / ReadDirectoryChangesW / FSEvents
+    Native,
+    /// Periodic scan comparing file contents, for network shares and bind mounts
+    Poll,
+    /// Native when a startup self-test sees events, poll otherwise
+    Auto,
+}
+
+/// Command run before a resource is reloaded, e.g. `pnpm build` for a TypeScript resource.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BuildHook {
+    /// Run through `sh -c` (`cmd /C` on Windows)
+    pub command: String,
+    /// Working directory relative to the resource root, the root itself by default
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_ms: u64,
+    /// Globs relative to the resource root, a change to a matching file runs the hook
+    pub sources: Vec<String>,
+    /// Globs of the files the hook writes, they never run it again
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+fn default_hook_timeout() -> u64 {
+    120_000
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFormat {
+    #[default]
+    Tap,
+    Junit,
+}
+
+/// Tests run after a resource was reloaded, e.g. `busted -o TAP` or `vitest run --reporter=junit`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TestRunner {
+    /// Run through `sh -c` (`cmd /C` on Windows)
+    pub command: String,
+    /// Working directory relative to the resource root, the root itself by default
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub format: TestFormat,
+    /// File written by the command relative to the resource root, its stdout is parsed when unset
+    #[serde(default)]
+    pub report: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LintFormat {
+    /// `luacheck --formatter plain --codes`
+    Luacheck,
+    /// `selene --display-style json2`
+    Selene,
+    /// `eslint -f json`
+    Eslint,
+}
+
+impl LintFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            LintFormat::Luacheck => "luacheck",
+            LintFormat::Selene => "selene",
+            LintFormat::Eslint => "eslint",
+        }
+    }
+}
+
+/// Linter run on every changed file it applies to.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Linter {
+    /// Run in the resource root, `{file}` is replaced by the file path, appended when missing
+    pub command: String,
+    pub format: LintFormat,
+    /// Globs relative to the resource root, e.g. `**/*.lua`
+    pub files: Vec<String>,
+    #[serde(default = "default_lint_timeout")]
+    pub timeout_ms: u64,
+}
+
+fn default_lint_timeout() -> u64 {
+    30_000
+}
+
+/// Stops reloading a resource on its own once its reloads keep failing: FXServer
+/// refused it or the resource printed a script error right after. It reloads again
+/// once reset from the UI, once the files changed since the trip lint without
+/// errors, or, for resources no linter covers, once a manual reload goes through.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CircuitBreaker {
+    /// Failed reloads within `window_ms` that trip the breaker, 0 disables it
+    pub failures: usize,
+    pub window_ms: u64,
+    /// How long after a reload a script error of the resource counts as its failure
+    pub error_grace_ms: u64,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failures: 3,
+            window_ms: 120_000,
+            error_grace_ms: 10_000,
+        }
+    }
+}
+
+/// Copies of the watched files of a resource, taken once a reload went through
+/// without error, that a rollback restores.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Snapshots {
+    pub enabled: bool,
+    /// Relative to the directory the watcher runs in
+    pub dir: String,
+    /// Snapshots kept per resource, the oldest are dropped first
+    pub keep: usize,
+    /// Rolls a resource back to its last snapshot once its circuit breaker trips,
+    /// never with the breaker disabled
+    pub auto_rollback: bool,
+}
+
+impl Default for Snapshots {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: ".hot-reload/snapshots".to_string(),
+            keep: 10,
+            auto_rollback: false,
+        }
+    }
+}
+
+/// JSON lines recording every change, reload and client request, rotated by size.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct History {
+    pub enabled: bool,
+    /// Relative to the directory the watcher runs in, rotated files get `.1`, `.2`, ... before the extension
+    pub path: String,
+    /// Size after which the file is rotated
+    pub max_bytes: u64,
+    /// Rotated files kept besides the current one
+    pub keep_files: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: ".hot-reload/history.jsonl".to_string(),
+            max_bytes: 5 * 1024 * 1024,
+            keep_files: 4,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct WatcherConfig {
     pub ws_host: String,
     pub ws_port: u16,
     pub fxserver_port: u16,
     pub resources_path: String,
+    /// Operator key of remote clients, they are all refused while it is empty
     pub api_key: String,
+    /// Additional API keys and what their clients may do, `api_key` is an operator key
+    pub api_keys: HashMap<String, Role>,
+    /// How long a file's size and mtime must stay unchanged before it is reloaded
+    pub stability_interval_ms: u64,
+    /// Same as `stability_interval_ms` for `.dll` files, which are written in chunks
+    pub dll_stability_interval_ms: u64,
+    pub watch_mode: WatchMode,
+    pub poll_interval_ms: u64,
+    /// Resources never restarted because one of their dependencies was reloaded
+    pub no_cascade: Vec<String>,
+    /// Per-resource reload strategy, resources not listed use `auto`
+    pub reload_strategies: HashMap<String, ReloadStrategy>,
+    /// Per-resource build run before reloading, a failed build cancels the reload
+    pub build_hooks: HashMap<String, BuildHook>,
+    /// Per-resource tests run once the resource was reloaded
+    pub test_runners: HashMap<String, TestRunner>,
+    /// Linters run on changed files, their diagnostics do not block reloads
+    pub linters: Vec<Linter>,
+    /// Mode the watcher starts in, shared by every client; `manual` waits for an apply
+    pub apply_mode: ApplyMode,
+    pub circuit_breaker: CircuitBreaker,
+    pub snapshots: Snapshots,
+    pub history: History,
 }
 
 impl Default for WatcherConfig {
@@ -17,6 +213,20 @@ impl Default for WatcherConfig {
             fxserver_port: 3091,
             resources_path: String::new(),
             api_key: String::new(),
+            api_keys: HashMap::new(),
+            stability_interval_ms: 300,
+            dll_stability_interval_ms: 1500,
+            watch_mode: WatchMode::Auto,
+            poll_interval_ms: 1000,
+            no_cascade: Vec::new(),
+            reload_strategies: HashMap::new(),
+            build_hooks: HashMap::new(),
+            test_runners: HashMap::new(),
+            linters: Vec::new(),
+            apply_mode: ApplyMode::Auto,
+            circuit_breaker: CircuitBreaker::default(),
+            snapshots: Snapshots::default(),
+            history: History::default(),
         }
     }
 }
@@ -27,15 +237,76 @@ impl WatcherConfig {
             serde_json::from_str(&content).unwrap_or_default()
         } else {
             let config = Self::default();
-            let _ = std::fs::write(
-                "config.hotwatch.json",
-                serde_json::to_string_pretty(&config).unwrap(),
-            );
+            let _ = config.save();
             config
         }
     }
 
+    /// Reads config.hotwatch.json without falling back to the defaults on a parse error.
+    pub fn load() -> Result<Self, String> {
+        let content = std::fs::read_to_string("config.hotwatch.json").map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    /// Rewrites one key of config.hotwatch.json and keeps the rest of the file as it is.
+    /// Nothing is written when the file is not a valid config.
+    pub fn save_key(key: &str, value: serde_json::Value) -> Result<(), String> {
+        Self::load()?;
+        let content = std::fs::read_to_string("config.hotwatch.json").map_err(|e| e.to_string())?;
+        let mut config: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        config[key] = value;
+        let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        std::fs::write("config.hotwatch.json", content).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::write(
+            "config.hotwatch.json",
+            serde_json::to_string_pretty(self).unwrap(),
+        )
+    }
+
+    pub fn watch_root(&self) -> PathBuf {
+        if self.resources_path.is_empty() {
+            PathBuf::from("resources")
+        } else {
+            PathBuf::from(&self.resources_path)
+        }
+    }
+
+    /// Role granted to a client presenting this key, `None` for an unknown key.
+    /// An empty key never matches, remote clients are refused until a key is set.
+    pub fn role_of(&self, api_key: &str) -> Option<Role> {
+        if api_key.is_empty() {
+            return None;
+        }
+        if api_key == self.api_key {
+            return Some(Role::Operator);
+        }
+        self.api_keys.get(api_key).copied()
+    }
+
     pub fn ws_url(&self) -> String {
         format!("ws://{}:{}", self.ws_host, self.ws_port)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_api_keys_grant_nothing() {
+        let mut config = WatcherConfig::default();
+        assert_eq!(config.role_of(""), None);
+        assert_eq!(config.role_of("guess"), None);
+
+        config.api_keys.insert(String::new(), Role::Viewer);
+        assert_eq!(config.role_of(""), None);
+
+        config.api_key = "secret".to_string();
+        config.api_keys.insert("look".to_string(), Role::Viewer);
+        assert_eq!(config.role_of(""), None);
+        assert_eq!(config.role_of("secret"), Some(Role::Operator));
+        assert_eq!(config.role_of("look"), Some(Role::Viewer));
+    }
+}
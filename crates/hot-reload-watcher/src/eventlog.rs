@@ -0,0 +1,46 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+use hot_reload_common::{PipelineLog, PipelineLogEntry};
+use tracing::debug;
+
+/// Ring buffer of what the reload pipeline did, oldest entries are dropped first.
+#[derive(Debug)]
+pub struct EventLog {
+    entries: VecDeque<PipelineLogEntry>,
+    capacity: usize,
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, stage: &str, message: impl Into<String>) {
+        let message = message.into();
+        debug!("[pipeline:{}] {}", stage, message);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(PipelineLogEntry {
+            timestamp_ms: now_ms(),
+            stage: stage.to_string(),
+            message,
+        });
+    }
+
+    pub fn snapshot(&self) -> PipelineLog {
+        PipelineLog {
+            entries: self.entries.iter().cloned().collect(),
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::state::WatcherState;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use hot_reload_common::{ResourceAction, ResourceChange, ResourceState, WatcherMessage};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, error, info, warn};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+pub type FxStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// How often the link tries to come back when nothing is being reloaded
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Messages the resource pushes on its own, anything else answers a change.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FxEvent {
+    Console { channel: String, message: String },
+    /// Answer to a `states` query and sent after a `refresh`
+    ResourceStates { states: HashMap<String, ResourceState> },
+    ResourceState { resource: String, state: ResourceState },
+}
+
+/// A console command sent by a client, run by the pipeline between two reloads.
+pub struct FxCommand {
+    pub id: u64,
+    pub command: String,
+    /// The connection of the client that asked, the result only goes there
+    pub reply: mpsc::UnboundedSender<WatcherMessage>,
+    pub user: String,
+}
+
+/// What clients ask the pipeline to send to FXServer.
+pub enum FxRequest {
+    Command(FxCommand),
+    Action { resources: Vec<String>, action: ResourceAction, user: String },
+    /// Changes held by a pause or a tripped breaker, reloaded once it is over
+    Resume(BTreeMap<String, ResourceChange>),
+    /// Resources whose changes collected in manual mode get reloaded
+    Apply { resources: Vec<String>, user: String },
+    /// Restores a snapshot, the latest when `None`. Without a user it follows a failed reload
+    Rollback { resource: String, snapshot: Option<u64>, user: Option<String> },
+}
+
+impl FxRequest {
+    /// Client that asked, `None` for what the watcher does on its own
+    pub fn user(&self) -> Option<String> {
+        match self {
+            FxRequest::Command(command) => Some(command.user.clone()),
+            FxRequest::Action { user, .. } | FxRequest::Apply { user, .. } => Some(user.clone()),
+            FxRequest::Rollback { user, .. } => user.clone(),
+            FxRequest::Resume(_) => None,
+        }
+    }
+}
+
+/// What the resource answers to a command.
+#[derive(Deserialize)]
+pub struct FxCommandReply {
+    #[serde(default)]
+    pub output: Vec<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// The single connection to the hot-reload resource running inside FXServer.
+/// It is opened on first use and opened again after a failure. A reader task
+/// relays the console output and hands the answers to the pending change.
+pub struct FxLink {
+    port: u16,
+    state: Arc<WatcherState>,
+    sink: Option<SplitSink<FxStream, Message>>,
+    replies: Option<mpsc::UnboundedReceiver<String>>,
+    reader: Option<JoinHandle<()>>,
+    last_attempt: Option<Instant>,
+    /// Set when a connection opens, until the pipeline asked for the resource states
+    reconnected: bool,
+}
+
+impl FxLink {
+    pub fn new(state: Arc<WatcherState>) -> Self {
+        Self {
+            port: state.config.fxserver_port,
+            state,
+            sink: None,
+            replies: None,
+            reader: None,
+            last_attempt: None,
+            reconnected: false,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.sink.is_some() && self.reader.as_ref().is_some_and(|reader| !reader.is_finished())
+    }
+
+    async fn connect(&mut self) -> Result<(), BoxError> {
+        if self.is_connected() {
+            return Ok(());
+        }
+        self.reset();
+        self.last_attempt = Some(Instant::now());
+
+        let fivem_url = format!("ws://localhost:{}", self.port);
+        debug!("🔌 Trying to connect to FXserver on {}", fivem_url);
+        let (ws_stream, _) = connect_async(&fivem_url).await?;
+        info!("✅ FXserver connection established!");
+
+        let (sink, stream) = ws_stream.split();
+        let (replies_tx, replies_rx) = mpsc::unbounded_channel();
+        self.reader = Some(tokio::spawn(read(stream, self.state.clone(), replies_tx)));
+        self.sink = Some(sink);
+        self.replies = Some(replies_rx);
+        self.reconnected = true;
+        Ok(())
+    }
+
+    /// Opens the connection again once in a while, so the console keeps
+    /// streaming after FXServer restarts even when nothing gets reloaded.
+    pub async fn keep_alive(&mut self) {
+        if self.is_connected() || self.last_attempt.is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL) {
+            return;
+        }
+        let _ = self.connect().await;
+    }
+
+    /// Whether a connection opened since the last call.
+    pub fn take_reconnected(&mut self) -> bool {
+        std::mem::take(&mut self.reconnected)
+    }
+
+    /// Asks for the state of every resource, the answer comes through the reader task.
+    pub async fn query_states(&mut self) {
+        if self.connect().await.is_err() {
+            return;
+        }
+        let Some(sink) = self.sink.as_mut() else {
+            return;
+        };
+        let message = serde_json::json!({ "type": "states" }).to_string();
+        if let Err(e) = sink.send(Message::Text(message)).await {
+            warn!("⚠️ FXserver connection lost: {}", e);
+            self.reset();
+        }
+    }
+
+    /// Drops the connection, the next send opens a new one.
+    pub fn reset(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+        self.sink = None;
+        self.replies = None;
+    }
+
+    /// Sends a change and waits for the resource's answer.
+    pub async fn send_change(&mut self, change: &ResourceChange) -> Option<String> {
+        let message = serde_json::to_string(change).ok()?;
+        self.request(message).await
+    }
+
+    /// Runs a console command and waits for the lines it printed.
+    pub async fn send_command(&mut self, command: &str) -> Option<FxCommandReply> {
+        let message = serde_json::json!({ "type": "command", "command": command }).to_string();
+        let response = self.request(message).await?;
+        match serde_json::from_str(&response) {
+            Ok(reply) => Some(reply),
+            Err(_) => Some(FxCommandReply {
+                output: vec![response],
+                error: None,
+            }),
+        }
+    }
+
+    async fn request(&mut self, message: String) -> Option<String> {
+        if let Err(e) = self.connect().await {
+            error!("❌ FXserver unreachable: {}", e);
+            return None;
+        }
+
+        let sink = self.sink.as_mut()?;
+        if let Err(e) = sink.send(Message::Text(message)).await {
+            warn!("⚠️ FXserver connection lost: {}", e);
+            self.reset();
+            return None;
+        }
+        info!("✅ Message sent to FXserver");
+
+        match self.replies.as_mut()?.recv().await {
+            Some(response) => Some(response),
+            None => {
+                warn!("⚠️ FXserver connection closed");
+                self.reset();
+                None
+            }
+        }
+    }
+}
+
+async fn read(mut stream: SplitStream<FxStream>, state: Arc<WatcherState>, replies: mpsc::UnboundedSender<String>) {
+    while let Some(Ok(message)) = stream.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        match serde_json::from_str::<FxEvent>(&text) {
+            Ok(FxEvent::Console { channel, message }) => state.console_output(&channel, &message),
+            Ok(FxEvent::ResourceStates { states }) => state.resource_states_received(states),
+            Ok(FxEvent::ResourceState { resource, state: resource_state }) => state.resource_state_changed(resource, resource_state),
+            Err(_) => {
+                let _ = replies.send(text);
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use crate::index::ResourceIndex;
+use crate::manifest::Manifest;
+use globset::{Glob, GlobMatcher};
+use hot_reload_common::{GraphNode, ResourceGraph};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::Path;
+use tracing::warn;
+
+/// Resources depending on each other through the `dependency` / `dependencies`
+/// directives of their manifests. Dependencies that are not resources of the
+/// tree (`/server:5181`, `/onesync`, resources living elsewhere) are kept so
+/// they can be reported, but never take part in the ordering.
+///
+/// Scripts loaded from another resource (`'@ox_lib/init.lua'`) are tracked as
+/// imports: the importer reloads when that file changes and always comes
+/// after the resource owning it.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+    imports: BTreeMap<String, Vec<Import>>,
+    /// Position of each resource in the start order, computed again whenever the graph changes
+    rank: HashMap<String, usize>,
+}
+
+/// A `@resource/path` entry of a manifest's script lists.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub resource: String,
+    pub path: String,
+    matcher: Option<GlobMatcher>,
+}
+
+impl PartialEq for Import {
+    fn eq(&self, other: &Self) -> bool {
+        self.resource == other.resource && self.path == other.path
+    }
+}
+
+impl Import {
+    fn parse(entry: &str) -> Option<Self> {
+        let (resource, path) = entry.strip_prefix('@')?.split_once('/')?;
+        let matcher = if path.contains(['*', '?', '[', '{']) {
+            Glob::new(path).ok().map(|glob| glob.compile_matcher())
+        } else {
+            None
+        };
+        Some(Self {
+            resource: resource.to_string(),
+            path: path.to_string(),
+            matcher,
+        })
+    }
+
+    fn matches(&self, resource: &str, relative_path: &str) -> bool {
+        if self.resource != resource {
+            return false;
+        }
+        let relative_path = relative_path.replace('\\', "/");
+        match &self.matcher {
+            Some(matcher) => matcher.is_match(&relative_path),
+            None => self.path == relative_path,
+        }
+    }
+}
+
+fn read_manifest(root: &Path) -> (BTreeSet<String>, Vec<Import>) {
+    match Manifest::load(root) {
+        Ok(manifest) => {
+            let dependencies = manifest
+                .values("dependency")
+                .into_iter()
+                .filter(|name| !name.starts_with('/'))
+                .map(String::from)
+                .collect();
+            let imports = manifest.scripts().into_iter().filter_map(Import::parse).collect();
+            (dependencies, imports)
+        }
+        Err(e) => {
+            warn!("⚠️ Unreadable manifest in {}: {}", root.display(), e);
+            (BTreeSet::new(), Vec::new())
+        }
+    }
+}
+
+impl DependencyGraph {
+    pub fn build(index: &ResourceIndex) -> Self {
+        let mut graph = Self::default();
+        for name in index.resource_names() {
+            if let Some(resource) = index.resource(&name) {
+                let (dependencies, imports) = read_manifest(&resource.root);
+                graph.dependencies.insert(name.clone(), dependencies);
+                graph.imports.insert(name, imports);
+            }
+        }
+        graph.rank = graph.topological_rank();
+        graph
+    }
+
+    /// Reads the manifest of one resource again, returns whether its
+    /// dependencies or imports changed.
+    pub fn update_resource(&mut self, name: &str, root: &Path) -> bool {
+        let (dependencies, imports) = read_manifest(root);
+        let dependencies_changed = self.dependencies.insert(name.to_string(), dependencies.clone()) != Some(dependencies);
+        let imports_changed = self.imports.insert(name.to_string(), imports.clone()) != Some(imports);
+        if dependencies_changed || imports_changed {
+            self.rank = self.topological_rank();
+        }
+        dependencies_changed || imports_changed
+    }
+
+    pub fn dependencies_of(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.dependencies
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|dependency| self.dependencies.contains_key(*dependency))
+            .map(String::as_str)
+    }
+
+    /// Resources declaring `name` as a dependency.
+    pub fn dependents_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.dependencies
+            .iter()
+            .filter(move |(_, dependencies)| dependencies.contains(name))
+            .map(|(dependent, _)| dependent.as_str())
+    }
+
+    /// Resources whose manifest loads `relative_path` from `resource`.
+    pub fn importers_of(&self, resource: &str, relative_path: &str) -> Vec<String> {
+        self.imports
+            .iter()
+            .filter(|(importer, imports)| {
+                importer.as_str() != resource && imports.iter().any(|import| import.matches(resource, relative_path))
+            })
+            .map(|(importer, _)| importer.clone())
+            .collect()
+    }
+
+    /// What `name` has to start after: its dependencies and the resources it imports from.
+    fn requirements_of<'a>(&'a self, name: &'a str) -> BTreeSet<&'a str> {
+        let imported = self
+            .imports
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|import| import.resource.as_str())
+            .filter(|resource| *resource != name && self.dependencies.contains_key(*resource));
+        self.dependencies_of(name).chain(imported).collect()
+    }
+
+    /// Resources to restart because one of `reloaded` did, with the resource that
+    /// pulled each of them in. Resources for which `skip` is true are left
+    /// running and the cascade does not go through them.
+    pub fn cascade(&self, reloaded: &BTreeSet<String>, skip: impl Fn(&str) -> bool) -> Vec<(String, String)> {
+        let mut restarted = Vec::new();
+        let mut seen: BTreeSet<&str> = reloaded.iter().map(String::as_str).collect();
+        let mut queue: VecDeque<&str> = reloaded.iter().map(String::as_str).collect();
+
+        while let Some(name) = queue.pop_front() {
+            for dependent in self.dependents_of(name) {
+                if skip(dependent) || !seen.insert(dependent) {
+                    continue;
+                }
+                restarted.push((dependent.to_string(), name.to_string()));
+                queue.push_back(dependent);
+            }
+        }
+        restarted
+    }
+
+    /// Sorts `names` so every resource comes after the resources it depends on,
+    /// directly or not. Resources caught in a cycle come last, by name.
+    pub fn order(&self, names: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut names: Vec<String> = names.into_iter().collect();
+        names.sort_by(|a, b| {
+            let rank_a = self.rank.get(a).copied().unwrap_or(usize::MAX);
+            let rank_b = self.rank.get(b).copied().unwrap_or(usize::MAX);
+            rank_a.cmp(&rank_b).then_with(|| a.cmp(b))
+        });
+        names
+    }
+
+    /// Kahn's algorithm over the requirements, ties broken by name. Resources
+    /// in a cycle, and the ones waiting on them, get no rank.
+    fn topological_rank(&self) -> HashMap<String, usize> {
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        let mut waiting: HashMap<&str, Vec<&str>> = HashMap::new();
+        for name in self.dependencies.keys() {
+            let requirements = self.requirements_of(name);
+            remaining.insert(name, requirements.len());
+            for requirement in requirements {
+                waiting.entry(requirement).or_default().push(name);
+            }
+        }
+        let mut ready: BTreeSet<&str> = remaining
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(name, _)| *name)
+            .collect();
+        let mut rank = HashMap::new();
+
+        while let Some(name) = ready.pop_first() {
+            rank.insert(name.to_string(), rank.len());
+            for dependent in waiting.get(name).into_iter().flatten() {
+                if let Some(count) = remaining.get_mut(dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(dependent);
+                    }
+                }
+            }
+        }
+        rank
+    }
+
+    /// The graph as sent to the clients, missing dependencies included.
+    pub fn snapshot(&self, no_cascade: &[String]) -> ResourceGraph {
+        let nodes = self
+            .dependencies
+            .iter()
+            .map(|(name, dependencies)| {
+                let imported: BTreeSet<&str> = self
+                    .imports
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .map(|import| import.resource.as_str())
+                    .filter(|resource| resource != name)
+                    .collect();
+                let missing = dependencies
+                    .iter()
+                    .map(String::as_str)
+                    .chain(imported.iter().copied())
+                    .filter(|resource| !self.dependencies.contains_key(*resource))
+                    .map(String::from)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+
+                GraphNode {
+                    name: name.clone(),
+                    dependencies: self.dependencies_of(name).map(String::from).collect(),
+                    imports: imported
+                        .into_iter()
+                        .filter(|resource| self.dependencies.contains_key(*resource))
+                        .map(String::from)
+                        .collect(),
+                    missing,
+                    no_cascade: no_cascade.contains(name),
+                }
+            })
+            .collect();
+        ResourceGraph { nodes }
+    }
+
+    /// Groups of resources depending on each other in a loop, found with Tarjan's algorithm.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        struct Tarjan<'a> {
+            graph: &'a DependencyGraph,
+            next_index: usize,
+            index: HashMap<&'a str, usize>,
+            low_link: HashMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: BTreeSet<&'a str>,
+            cycles: Vec<Vec<String>>,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn visit(&mut self, name: &'a str) {
+                self.index.insert(name, self.next_index);
+                self.low_link.insert(name, self.next_index);
+                self.next_index += 1;
+                self.stack.push(name);
+                self.on_stack.insert(name);
+
+                for dependency in self.graph.dependencies_of(name) {
+                    if !self.index.contains_key(dependency) {
+                        self.visit(dependency);
+                        let low = self.low_link[name].min(self.low_link[dependency]);
+                        self.low_link.insert(name, low);
+                    } else if self.on_stack.contains(dependency) {
+                        let low = self.low_link[name].min(self.index[dependency]);
+                        self.low_link.insert(name, low);
+                    }
+                }
+
+                if self.low_link[name] != self.index[name] {
+                    return;
+                }
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == name {
+                        break;
+                    }
+                }
+                let self_loop = self.graph.dependencies_of(name).any(|dependency| dependency == name);
+                if component.len() > 1 || self_loop {
+                    component.sort();
+                    self.cycles.push(component);
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            next_index: 0,
+            index: HashMap::new(),
+            low_link: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            cycles: Vec::new(),
+        };
+        for name in self.dependencies.keys() {
+            if !tarjan.index.contains_key(name.as_str()) {
+                tarjan.visit(name);
+            }
+        }
+
+        let mut cycles = tarjan.cycles;
+        cycles.sort();
+        cycles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(resources: &[(&str, &[&str], &[&str])]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (name, dependencies, scripts) in resources {
+            graph.dependencies.insert(name.to_string(), dependencies.iter().map(|d| d.to_string()).collect());
+            graph.imports.insert(name.to_string(), scripts.iter().filter_map(|s| Import::parse(s)).collect());
+        }
+        graph.rank = graph.topological_rank();
+        graph
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn order_follows_dependencies_and_imports() {
+        let graph = graph(&[
+            ("app", &["core", "lib", "not_in_tree"], &["client.lua"]),
+            ("core", &[], &[]),
+            ("lib", &["core"], &[]),
+            ("ui", &[], &["@lib/init.lua", "@ui/self.lua"]),
+        ]);
+        assert_eq!(graph.order(names(&["ui", "app", "lib", "core"])), ["core", "lib", "app", "ui"]);
+        assert_eq!(graph.importers_of("lib", "init.lua"), ["ui"]);
+        assert!(graph.importers_of("ui", "self.lua").is_empty());
+    }
+
+    #[test]
+    fn cycles_come_last() {
+        let graph = graph(&[("a", &["b"], &[]), ("b", &["a"], &[]), ("base", &[], &[]), ("c", &["base"], &[])]);
+        assert_eq!(graph.order(names(&["b", "a", "c", "base"])), ["base", "c", "a", "b"]);
+    }
+
+    #[test]
+    fn tarjan_finds_loops_and_self_loops() {
+        let graph = graph(&[
+            ("a", &["b"], &[]),
+            ("b", &["c"], &[]),
+            ("c", &["a"], &[]),
+            ("d", &["a"], &[]),
+            ("e", &["e"], &[]),
+            ("f", &["g"], &[]),
+            ("g", &[], &[]),
+        ]);
+        assert_eq!(graph.cycles(), [names(&["a", "b", "c"]), names(&["e"])]);
+        assert!(DependencyGraph::default().cycles().is_empty());
+    }
+
+    #[test]
+    fn cascade_stops_at_skipped_resources() {
+        let graph = graph(&[("core", &[], &[]), ("lib", &["core"], &[]), ("app", &["lib"], &[]), ("admin", &["core"], &[])]);
+        let reloaded = BTreeSet::from(["core".to_string()]);
+        let cascade = graph.cascade(&reloaded, |_| false);
+        assert_eq!(cascade.len(), 3);
+        assert!(cascade.contains(&("app".to_string(), "lib".to_string())));
+        assert_eq!(graph.cascade(&reloaded, |name| name == "lib"), [("admin".to_string(), "core".to_string())]);
+    }
+
+    #[test]
+    fn order_follows_manifest_updates() {
+        let root = std::env::temp_dir().join(format!("hot-reload-graph-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut graph = graph(&[("alpha", &[], &[]), ("beta", &[], &[])]);
+        assert_eq!(graph.order(names(&["beta", "alpha"])), ["alpha", "beta"]);
+
+        std::fs::write(root.join("fxmanifest.lua"), "dependency 'beta'").unwrap();
+        assert!(graph.update_resource("alpha", &root));
+        assert_eq!(graph.order(names(&["beta", "alpha"])), ["beta", "alpha"]);
+        assert!(!graph.update_resource("alpha", &root));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use crate::config::History;
+use hot_reload_common::{HistoryEntry, HistoryPage};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const DEFAULT_QUERY_LIMIT: usize = 500;
+const MAX_QUERY_LIMIT: usize = 5000;
+
+/// Filters of a history query, every one is optional.
+pub struct HistoryQuery {
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+    pub resource: Option<String>,
+    /// Part of the user name, case insensitive
+    pub user: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.from_ms.is_none_or(|from| entry.timestamp_ms >= from)
+            && self.to_ms.is_none_or(|to| entry.timestamp_ms <= to)
+            && self.resource.as_ref().is_none_or(|resource| entry.resources.contains(resource))
+            && self.user.as_ref().is_none_or(|user| {
+                entry.user.as_ref().is_some_and(|name| name.to_lowercase().contains(&user.to_lowercase()))
+            })
+    }
+}
+
+/// `history.jsonl` -> `history.1.jsonl`, the current file for 0.
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("history");
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, n, ext),
+        None => format!("{}.{}", stem, n),
+    };
+    path.with_file_name(name)
+}
+
+/// Appends history entries to a JSON lines file. Once it is over `max_bytes`
+/// it becomes `.1`, the previous `.1` becomes `.2` and so on up to `keep_files`.
+pub struct HistoryLog {
+    config: History,
+    file: Option<File>,
+    size: u64,
+}
+
+impl HistoryLog {
+    pub fn new(config: &History) -> Self {
+        Self {
+            config: config.clone(),
+            file: None,
+            size: 0,
+        }
+    }
+
+    fn open(&mut self) -> std::io::Result<&mut File> {
+        if self.file.is_none() {
+            let path = Path::new(&self.config.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("opened above"))
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let path = Path::new(&self.config.path);
+        if self.config.keep_files == 0 {
+            return std::fs::remove_file(path);
+        }
+        for n in (0..self.config.keep_files).rev() {
+            let from = numbered(path, n);
+            if from.exists() {
+                std::fs::rename(from, numbered(path, n + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn append(&mut self, entry: &HistoryEntry) {
+        if !self.config.enabled {
+            return;
+        }
+        let Ok(mut line) = serde_json::to_string(entry) else {
+            return;
+        };
+        line.push('\n');
+        let written = self.open().and_then(|file| file.write_all(line.as_bytes()));
+        if let Err(e) = written {
+            warn!("⚠️ Could not write the history to {}: {}", self.config.path, e);
+            self.file = None;
+            return;
+        }
+        self.size += line.len() as u64;
+        if self.size >= self.config.max_bytes {
+            if let Err(e) = self.rotate() {
+                warn!("⚠️ Could not rotate the history: {}", e);
+            }
+        }
+    }
+}
+
+/// Reads the history from the newest entry back, rotated files included.
+pub fn query(config: &History, query: &HistoryQuery) -> HistoryPage {
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT);
+    let mut page = HistoryPage {
+        entries: Vec::new(),
+        truncated: false,
+    };
+    let path = Path::new(&config.path);
+    for n in 0..=config.keep_files {
+        let Ok(content) = std::fs::read_to_string(numbered(path, n)) else {
+            continue;
+        };
+        // A line being written right now is incomplete and skipped
+        let matching = content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+            .filter(|entry| query.matches(entry));
+        for entry in matching {
+            if page.entries.len() == limit {
+                page.truncated = true;
+                return page;
+            }
+            page.entries.push(entry);
+        }
+    }
+    page
+}
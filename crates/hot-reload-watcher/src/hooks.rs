@@ -0,0 +1,182 @@
+use crate::config::BuildHook;
+use crate::state::WatcherState;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use hot_reload_common::{BuildFinished, BuildOutput, BuildStarted, OutputStream, WatcherMessage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+/// A build hook with its globs compiled.
+pub struct Hook {
+    pub config: BuildHook,
+    sources: GlobSet,
+    outputs: GlobSet,
+}
+
+/// Sent back to the pipeline once a build is over.
+#[derive(Debug)]
+pub struct BuildDone {
+    pub resource: String,
+    /// Source file whose change started the build
+    pub trigger: PathBuf,
+    pub success: bool,
+}
+
+pub fn glob_set(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build()
+}
+
+impl Hook {
+    pub fn new(config: BuildHook) -> Result<Self, globset::Error> {
+        Ok(Self {
+            sources: glob_set(&config.sources)?,
+            outputs: glob_set(&config.outputs)?,
+            config,
+        })
+    }
+
+    pub fn is_output(&self, relative_path: &str) -> bool {
+        self.outputs.is_match(relative_path.replace('\\', "/"))
+    }
+
+    /// Whether a change to this file runs the build, outputs never do.
+    pub fn is_source(&self, relative_path: &str) -> bool {
+        let relative_path = relative_path.replace('\\', "/");
+        !self.outputs.is_match(&relative_path) && self.sources.is_match(&relative_path)
+    }
+}
+
+/// Compiles the hooks of the config, hooks with an invalid glob are left out.
+pub fn load_hooks(hooks: &HashMap<String, BuildHook>) -> HashMap<String, Hook> {
+    hooks
+        .iter()
+        .filter_map(|(resource, config)| match Hook::new(config.clone()) {
+            Ok(hook) => Some((resource.clone(), hook)),
+            Err(e) => {
+                error!("❌ Invalid glob in the build hook of {}: {}", resource, e);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+async fn forward_lines(state: Arc<WatcherState>, resource: String, stream: OutputStream, output: impl AsyncRead + Unpin) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        state.broadcast(WatcherMessage::BuildOutput(BuildOutput {
+            resource: resource.clone(),
+            stream,
+            line,
+        }));
+    }
+}
+
+async fn run(state: &Arc<WatcherState>, resource: &str, root: &Path, hook: &BuildHook) -> Result<(), String> {
+    let cwd = match &hook.cwd {
+        Some(cwd) => root.join(cwd),
+        None => root.to_path_buf(),
+    };
+
+    let mut child = shell(&hook.command)
+        .current_dir(&cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("cannot start `{}` in {}: {}", hook.command, cwd.display(), e))?;
+
+    let stdout = child.stdout.take().map(|out| {
+        tokio::spawn(forward_lines(state.clone(), resource.to_string(), OutputStream::Stdout, out))
+    });
+    let stderr = child.stderr.take().map(|err| {
+        tokio::spawn(forward_lines(state.clone(), resource.to_string(), OutputStream::Stderr, err))
+    });
+
+    let status = match tokio::time::timeout(Duration::from_millis(hook.timeout_ms), child.wait()).await {
+        Ok(status) => status.map_err(|e| e.to_string())?,
+        Err(_) => {
+            let _ = child.kill().await;
+            return Err(format!("timed out after {} ms", hook.timeout_ms));
+        }
+    };
+
+    // Let the last lines reach the clients before the result
+    for reader in [stdout, stderr].into_iter().flatten() {
+        let _ = reader.await;
+    }
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("exited with {}", status))
+    }
+}
+
+/// Runs the hook of `resource` in the background and reports to `done`.
+pub fn spawn_build(
+    state: Arc<WatcherState>,
+    resource: String,
+    root: PathBuf,
+    hook: BuildHook,
+    trigger: PathBuf,
+    done: mpsc::UnboundedSender<BuildDone>,
+) {
+    tokio::spawn(async move {
+        info!("🔨 Building {}: {}", resource, hook.command);
+        state.log("build", format!("{}: {}", resource, hook.command));
+        state.broadcast(WatcherMessage::BuildStarted(BuildStarted {
+            resource: resource.clone(),
+            command: hook.command.clone(),
+        }));
+
+        let started = Instant::now();
+        let result = run(&state, &resource, &root, &hook).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        match &result {
+            Ok(()) => {
+                info!("✅ Build of {} finished in {} ms", resource, duration_ms);
+                state.log("build", format!("{} succeeded in {} ms", resource, duration_ms));
+            }
+            Err(e) => {
+                warn!("❌ Build of {} failed, reload cancelled: {}", resource, e);
+                state.log("build", format!("{} failed: {}", resource, e));
+            }
+        }
+        state.broadcast(WatcherMessage::BuildFinished(BuildFinished {
+            resource: resource.clone(),
+            success: result.is_ok(),
+            duration_ms,
+            error: result.as_ref().err().cloned(),
+        }));
+
+        let _ = done.send(BuildDone {
+            resource,
+            trigger,
+            success: result.is_ok(),
+        });
+    });
+}
@@ -0,0 +1,281 @@
+use crate::scanner::list_resource_files;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use hot_reload_common::{ChangeType, TreeDelta};
+use xxhash_rust::xxh3::xxh3_64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedResource {
+    pub root: PathBuf,
+    /// Watched files keyed by their path relative to `root`
+    pub files: BTreeMap<String, FileEntry>,
+}
+
+/// Everything the watcher knows about the resources on disk: where each
+/// resource lives and the last seen size, mtime and content hash of every
+/// watched file.
+#[derive(Debug, Default, Clone)]
+pub struct ResourceIndex {
+    resources: HashMap<String, IndexedResource>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentState {
+    Changed,
+    Unchanged,
+    Unreadable,
+}
+
+/// What a rescan of one resource found compared to the index.
+#[derive(Debug, Default)]
+pub struct ResourceDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+/// Canonical form of a path, files that are gone included: the closest parent
+/// that still exists is canonicalized and the rest joined back, so removals
+/// resolve symlinks the same way as the canonicalized resource roots.
+pub fn normalize(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    let canonical = loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            break canonical;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return strip_verbatim(absolute),
+        }
+    };
+    strip_verbatim(missing.into_iter().rev().fold(canonical, |path, name| path.join(name)))
+}
+
+/// `\\?\C:\x` -> `C:\x` and `\\?\UNC\server\x` -> `\\server\x`, like dunce does.
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    let Some(text) = path.to_str() else {
+        return path;
+    };
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", share));
+    }
+    match text.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => path,
+    }
+}
+
+pub fn read_entry(path: &Path) -> Option<FileEntry> {
+    let meta = std::fs::metadata(path).ok()?;
+    let bytes = std::fs::read(path).ok()?;
+    Some(FileEntry {
+        size: meta.len(),
+        modified: meta.modified().ok(),
+        hash: xxh3_64(&bytes),
+    })
+}
+
+impl ResourceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// The change to dispatch for the whole resource, adds and removes need a `refresh`.
+    pub fn change_type(&self) -> ChangeType {
+        if !self.added.is_empty() {
+            ChangeType::FileAdded
+        } else if !self.removed.is_empty() {
+            ChangeType::FileRemoved
+        } else {
+            ChangeType::FileModified
+        }
+    }
+
+    pub fn tree_delta(&self, resource_name: &str) -> TreeDelta {
+        TreeDelta {
+            resource_name: resource_name.to_string(),
+            added: self.added.clone(),
+            removed: self.removed.clone(),
+        }
+    }
+}
+
+impl ResourceIndex {
+    pub fn insert_resource(&mut self, name: String, root: &Path) {
+        self.resources.insert(name, IndexedResource {
+            root: normalize(root),
+            files: BTreeMap::new(),
+        });
+    }
+
+    pub fn remove_resource(&mut self, name: &str) -> Option<IndexedResource> {
+        self.resources.remove(name)
+    }
+
+    pub fn resource(&self, name: &str) -> Option<&IndexedResource> {
+        self.resources.get(name)
+    }
+
+    pub fn resource_names(&self) -> Vec<String> {
+        self.resources.keys().cloned().collect()
+    }
+
+    pub fn insert_file(&mut self, resource_name: &str, relative_path: String, entry: FileEntry) {
+        if let Some(resource) = self.resources.get_mut(resource_name) {
+            resource.files.insert(relative_path, entry);
+        }
+    }
+
+    /// Finds the resource owning `path` and the path relative to its root.
+    /// Nested resources win over the resource they sit in. `path` has to be
+    /// [`normalize`]d first, before the index is locked.
+    pub fn locate(&self, path: &Path) -> Option<(String, String)> {
+        self.resources
+            .iter()
+            .filter_map(|(name, resource)| {
+                path.strip_prefix(&resource.root)
+                    .ok()
+                    .map(|relative| (name, resource.root.components().count(), relative))
+            })
+            .max_by_key(|(_, depth, _)| *depth)
+            .map(|(name, _, relative)| (name.clone(), relative.to_string_lossy().to_string()))
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.locate(path)
+            .map(|(name, relative)| self.resources[&name].files.contains_key(&relative))
+            .unwrap_or(false)
+    }
+
+    /// Reads the file again and records it, telling whether its content
+    /// actually differs from what was indexed.
+    pub fn update(&mut self, path: &Path) -> ContentState {
+        let Some((name, relative)) = self.locate(path) else {
+            return ContentState::Unreadable;
+        };
+        let Some(entry) = read_entry(path) else {
+            return ContentState::Unreadable;
+        };
+        let resource = self.resources.get_mut(&name).expect("located resource");
+        match resource.files.insert(relative, entry.clone()) {
+            Some(previous) if previous.hash == entry.hash => ContentState::Unchanged,
+            _ => ContentState::Changed,
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        if let Some((name, relative)) = self.locate(path) {
+            if let Some(resource) = self.resources.get_mut(&name) {
+                resource.files.remove(&relative);
+            }
+        }
+    }
+
+    /// Lists the resource's files again and brings the index up to date.
+    /// Files whose size and mtime did not move are not hashed again.
+    pub fn rescan_resource(&mut self, name: &str) -> ResourceDiff {
+        let mut diff = ResourceDiff::default();
+        let Some(resource) = self.resources.get_mut(name) else {
+            return diff;
+        };
+
+        let on_disk: BTreeMap<String, PathBuf> = list_resource_files(&resource.root).into_iter().collect();
+
+        let removed: Vec<String> = resource.files
+            .keys()
+            .filter(|relative| !on_disk.contains_key(*relative))
+            .cloned()
+            .collect();
+        for relative in removed {
+            resource.files.remove(&relative);
+            diff.removed.push(relative);
+        }
+
+        for (relative, path) in on_disk {
+            let Ok(meta) = std::fs::metadata(&path) else {
+                continue;
+            };
+            let modified = meta.modified().ok();
+            match resource.files.get(&relative) {
+                Some(known) if known.size == meta.len() && known.modified == modified => {}
+                Some(known) => {
+                    if let Some(entry) = read_entry(&path) {
+                        if entry.hash != known.hash {
+                            diff.modified.push(relative.clone());
+                        }
+                        resource.files.insert(relative, entry);
+                    }
+                }
+                None => {
+                    if let Some(entry) = read_entry(&path) {
+                        resource.files.insert(relative.clone(), entry);
+                        diff.added.push(relative);
+                    }
+                }
+            }
+        }
+
+        diff
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.resources.values().map(|resource| resource.files.len()).sum()
+    }
+
+    /// The resource tree sent to clients, resources without watched files are left out.
+    pub fn tree(&self) -> HashMap<String, Vec<String>> {
+        self.resources
+            .iter()
+            .filter(|(_, resource)| !resource.files.is_empty())
+            .map(|(name, resource)| {
+                let mut files: Vec<String> = resource.files.keys().cloned().collect();
+                files.sort_by_cached_key(|file| file.to_lowercase());
+                (name.clone(), files)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_removed_files_under_a_symlinked_root() {
+        let base = std::env::temp_dir().join(format!("hot-reload-index-{}", std::process::id()));
+        let real = base.join("real");
+        std::fs::create_dir_all(real.join("client")).unwrap();
+        std::fs::write(real.join("client/main.lua"), "print(1)").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&real, base.join("link")).unwrap();
+        #[cfg(not(unix))]
+        std::fs::create_dir_all(base.join("link")).unwrap();
+
+        let mut index = ResourceIndex::default();
+        index.insert_resource("demo".to_string(), &base.join("real"));
+        std::fs::remove_file(real.join("client/main.lua")).unwrap();
+
+        let expected = Some(("demo".to_string(), Path::new("client").join("main.lua").to_string_lossy().to_string()));
+        assert_eq!(index.locate(&normalize(&real.join("client/main.lua"))), expected);
+        #[cfg(unix)]
+        assert_eq!(index.locate(&normalize(&base.join("link/client/main.lua"))), expected);
+        assert_eq!(index.locate(&normalize(&real.join("gone/deeper/file.lua"))).map(|(name, _)| name), Some("demo".to_string()));
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -1,5 +1,26 @@
 mod watcher;
+mod backend;
+mod breaker;
+mod cli;
 mod config;
+mod eventlog;
+mod fx;
+mod graph;
+mod history;
+mod hooks;
+mod index;
+mod lint;
+mod manifest;
+mod pipeline;
+mod resync;
+mod scanner;
+mod settle;
+mod snapshots;
+mod sourcemaps;
+mod state;
+mod stats;
+mod testrunner;
 
-pub use config::WatcherConfig;
+pub use cli::{control, Cli, Command};
+pub use config::{WatchMode, WatcherConfig};
 pub use watcher::run;
\ No newline at end of file
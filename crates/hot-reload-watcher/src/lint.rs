@@ -0,0 +1,243 @@
+use crate::config::{LintFormat, Linter};
+use crate::hooks::{glob_set, shell};
+use crate::state::WatcherState;
+use globset::GlobSet;
+use hot_reload_common::{Diagnostic, LintReport, Severity};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+/// A linter with its file globs compiled.
+pub struct CompiledLinter {
+    pub config: Linter,
+    files: GlobSet,
+}
+
+impl CompiledLinter {
+    pub fn applies_to(&self, relative_path: &str) -> bool {
+        self.files.is_match(relative_path.replace('\\', "/"))
+    }
+}
+
+/// Compiles the linters of the config, linters with an invalid glob are left out.
+pub fn load_linters(linters: &[Linter]) -> Vec<CompiledLinter> {
+    linters
+        .iter()
+        .filter_map(|linter| match glob_set(&linter.files) {
+            Ok(files) => Some(CompiledLinter {
+                config: linter.clone(),
+                files,
+            }),
+            Err(e) => {
+                error!("❌ Invalid glob in the {} linter: {}", linter.format.name(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn quote(path: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+/// `path:line:column[-end]: (W211) message`, the path may contain `:` on Windows.
+fn parse_luacheck(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (location, message) = line.split_once(": ")?;
+            let mut parts = location.rsplitn(3, ':');
+            let column = parts.next()?.split('-').next()?.parse().ok()?;
+            let line = parts.next()?.parse().ok()?;
+            parts.next()?;
+
+            let (code, message) = match message.strip_prefix('(').and_then(|m| m.split_once(") ")) {
+                Some((code, message)) => (Some(code.to_string()), message),
+                None => (None, message),
+            };
+            // Codes starting with E are syntax errors, W are warnings
+            let severity = match &code {
+                Some(code) if code.starts_with('E') => Severity::Error,
+                _ => Severity::Warning,
+            };
+            Some(Diagnostic {
+                severity,
+                line,
+                column,
+                code,
+                message: message.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// One JSON object per line, positions are 0-based.
+fn parse_selene(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|value| value["type"].as_str().unwrap_or("Diagnostic") == "Diagnostic")
+        .filter_map(|value| {
+            let message = value["message"].as_str()?.to_string();
+            let span = &value["primary_label"]["span"];
+            let severity = match value["severity"].as_str() {
+                Some("Error") => Severity::Error,
+                Some("Warning") => Severity::Warning,
+                _ => Severity::Info,
+            };
+            Some(Diagnostic {
+                severity,
+                line: span["start_line"].as_u64().map(|l| l as u32 + 1).unwrap_or(0),
+                column: span["start_column"].as_u64().map(|c| c as u32 + 1).unwrap_or(0),
+                code: value["code"].as_str().map(String::from),
+                message,
+            })
+        })
+        .collect()
+}
+
+/// `[{ "filePath": ..., "messages": [{ "severity": 1 | 2, ... }] }]`
+fn parse_eslint(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let results: Value = serde_json::from_str(output.trim()).map_err(|e| format!("invalid eslint output: {}", e))?;
+    let messages = results
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|result| result["messages"].as_array().cloned().unwrap_or_default());
+
+    Ok(messages
+        .filter_map(|message| {
+            Some(Diagnostic {
+                severity: match message["severity"].as_u64() {
+                    Some(2) => Severity::Error,
+                    Some(1) => Severity::Warning,
+                    _ => Severity::Info,
+                },
+                line: message["line"].as_u64().unwrap_or(0) as u32,
+                column: message["column"].as_u64().unwrap_or(0) as u32,
+                code: message["ruleId"].as_str().map(String::from),
+                message: message["message"].as_str()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+async fn run(root: &Path, relative_path: &str, linter: &Linter) -> Result<Vec<Diagnostic>, String> {
+    let file = quote(relative_path);
+    let command = if linter.command.contains("{file}") {
+        linter.command.replace("{file}", &file)
+    } else {
+        format!("{} {}", linter.command, file)
+    };
+
+    let child = shell(&command)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("cannot start `{}`: {}", command, e))?;
+
+    let output = tokio::time::timeout(Duration::from_millis(linter.timeout_ms), child.wait_with_output())
+        .await
+        .map_err(|_| format!("timed out after {} ms", linter.timeout_ms))?
+        .map_err(|e| e.to_string())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let diagnostics = match linter.format {
+        LintFormat::Luacheck => parse_luacheck(&stdout),
+        LintFormat::Selene => parse_selene(&stdout),
+        LintFormat::Eslint if stdout.trim().is_empty() => Vec::new(),
+        LintFormat::Eslint => parse_eslint(&stdout)?,
+    };
+
+    // Linters exit with an error when they find something, only a silent failure is one
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if diagnostics.is_empty() && !output.status.success() && !stderr.trim().is_empty() {
+        return Err(format!("exited with {}: {}", output.status, stderr.trim()));
+    }
+    Ok(diagnostics)
+}
+
+/// Lints one file in the background, aborting the returned handle kills the linter.
+pub fn spawn_lint(
+    state: Arc<WatcherState>,
+    resource: String,
+    root: PathBuf,
+    relative_path: String,
+    linter: Linter,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let source = linter.format.name();
+        match run(&root, &relative_path, &linter).await {
+            Ok(diagnostics) => {
+                state.log("lint", format!("{}/{}: {} issue(s) from {}", resource, relative_path, diagnostics.len(), source));
+                state.lint_finished(LintReport {
+                    resource,
+                    file: relative_path.replace('\\', "/"),
+                    source: source.to_string(),
+                    diagnostics,
+                });
+            }
+            Err(e) => {
+                warn!("⚠️ {} failed on {}/{}: {}", source, resource, relative_path, e);
+                state.log("lint", format!("{} failed on {}/{}: {}", source, resource, relative_path, e));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luacheck_plain_with_windows_paths() {
+        let output = "\
+C:\\server\\resources\\myres\\client.lua:3:7-9: (W211) unused variable 'ped'
+C:\\server\\resources\\myres\\client.lua:12:1: (E011) expected 'end' (to close 'function' at line 8) near <eof>
+client.lua:4:5-12: (W113) accessing undefined variable 'Citizen: Wait'
+
+Total: 2 warnings / 1 error in 1 file";
+        let diagnostics = parse_luacheck(output);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 7));
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("W211"));
+        assert_eq!(diagnostics[0].message, "unused variable 'ped'");
+        assert_eq!((diagnostics[1].line, diagnostics[1].severity), (12, Severity::Error));
+        assert_eq!(diagnostics[2].message, "accessing undefined variable 'Citizen: Wait'");
+    }
+
+    #[test]
+    fn selene_json2() {
+        let output = r#"{"type":"Diagnostic","severity":"Warning","code":"unused_variable","message":"ped is assigned a value, but never used","primary_label":{"span":{"start":6,"start_line":2,"start_column":6,"end":9,"end_line":2,"end_column":9},"message":""},"notes":[],"secondary_labels":[]}
+{"type":"Diagnostic","severity":"Error","code":"parse_error","message":"unexpected token `end`","primary_label":{"span":{"start":40,"start_line":7,"start_column":0,"end":43,"end_line":7,"end_column":3},"message":""},"notes":[],"secondary_labels":[]}
+{"type":"Summary","errors":1,"warnings":1,"parse_errors":0}"#;
+        let diagnostics = parse_selene(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 7));
+        assert_eq!(diagnostics[0].code.as_deref(), Some("unused_variable"));
+        assert_eq!((diagnostics[1].line, diagnostics[1].column, diagnostics[1].severity), (8, 1, Severity::Error));
+    }
+
+    #[test]
+    fn eslint_json() {
+        let output = r#"[{"filePath":"/srv/resources/jsres/src/server.ts","messages":[{"ruleId":"no-unused-vars","severity":1,"message":"'ped' is defined but never used.","line":3,"column":7,"nodeType":"Identifier","endLine":3,"endColumn":10},{"ruleId":null,"fatal":true,"severity":2,"message":"Parsing error: ')' expected.","line":9,"column":1}],"suppressedMessages":[],"errorCount":1,"fatalErrorCount":1,"warningCount":1,"fixableErrorCount":0,"fixableWarningCount":0,"usedDeprecatedRules":[]}]
+"#;
+        let diagnostics = parse_eslint(output).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].severity, diagnostics[0].code.as_deref()), (Severity::Warning, Some("no-unused-vars")));
+        assert_eq!((diagnostics[1].line, diagnostics[1].severity, diagnostics[1].code.as_deref()), (9, Severity::Error, None));
+        assert!(parse_eslint("Oops! Something went wrong!").is_err());
+    }
+}
@@ -1,10 +1,22 @@
-use hot_reload_watcher::WatcherConfig;
+use clap::Parser;
+use hot_reload_watcher::{Cli, Command, WatcherConfig};
 use tracing::error;
 use std::path::Path;
 
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+
+    if let Some(command) = cli.command.filter(|command| !matches!(command, Command::Run)) {
+        let config = WatcherConfig::load_or_create();
+        if let Err(e) = hot_reload_watcher::control(&config, command).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if !Path::new("resources").is_dir() {
         error!("The 'resources' directory does not exist in the current directory!");
         std::process::exit(1);
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const MANIFEST_FILES: [&str; 2] = ["fxmanifest.lua", "__resource.lua"];
+
+/// Directives read from a resource manifest. Only the parts the watcher cares
+/// about are kept, everything else in the file is skipped.
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    pub directives: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Open,
+    Close,
+    Other,
+}
+
+impl Manifest {
+    pub fn find(resource_path: &Path) -> Option<std::path::PathBuf> {
+        MANIFEST_FILES
+            .iter()
+            .map(|name| resource_path.join(name))
+            .find(|path| path.exists())
+    }
+
+    pub fn load(resource_path: &Path) -> std::io::Result<Self> {
+        let path = Self::find(resource_path)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no manifest"))?;
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn parse(source: &str) -> Self {
+        let tokens = tokenize(source);
+        let mut directives: HashMap<String, Vec<String>> = HashMap::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let Token::Ident(name) = &tokens[i] else {
+                i += 1;
+                continue;
+            };
+            i += 1;
+
+            match tokens.get(i) {
+                Some(Token::Str(value)) => {
+                    directives.entry(name.clone()).or_default().push(value.clone());
+                    i += 1;
+                }
+                Some(Token::Open) => {
+                    // `name { 'a', 'b' }` or `name('a')`, nested tables are flattened
+                    let mut depth = 0;
+                    while let Some(token) = tokens.get(i) {
+                        match token {
+                            Token::Open => depth += 1,
+                            Token::Close => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    i += 1;
+                                    break;
+                                }
+                            }
+                            Token::Str(value) => {
+                                directives.entry(name.clone()).or_default().push(value.clone());
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { directives }
+    }
+
+    /// Values of a directive, merging its singular and plural spelling
+    /// (`server_script` / `server_scripts`, `dependency` / `dependencies`).
+    pub fn values(&self, directive: &str) -> Vec<&str> {
+        let plural = match directive.strip_suffix('y') {
+            Some(stem) => format!("{}ies", stem),
+            None => format!("{}s", directive),
+        };
+        [directive.to_string(), plural]
+            .iter()
+            .filter_map(|key| self.directives.get(key))
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn scripts(&self) -> Vec<&str> {
+        ["client_script", "server_script", "shared_script"]
+            .iter()
+            .flat_map(|directive| self.values(directive))
+            .collect()
+    }
+
+    pub fn has_scripts(&self) -> bool {
+        !self.scripts().is_empty()
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' || c == ';' {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            i += 2;
+            if let Some((_, end)) = long_bracket(&chars, i) {
+                i = end;
+            } else {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+        } else if c == '[' && matches!(chars.get(i + 1), Some('[') | Some('=')) {
+            let start = i;
+            match long_bracket(&chars, i) {
+                Some((close, end)) => {
+                    let level = chars[start + 1..].iter().take_while(|&&c| c == '=').count();
+                    let text: String = chars[start + level + 2..close].iter().collect();
+                    tokens.push(Token::Str(text));
+                    i = end;
+                }
+                None => {
+                    tokens.push(Token::Other);
+                    i += 1;
+                }
+            }
+        } else if c == '\'' || c == '"' {
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c && chars[i] != '\n' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                text.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Str(text));
+        } else if c == '{' || c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == '}' || c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                ident.push(chars[i]);
+                i += 1;
+            }
+            tokens.push(Token::Ident(ident));
+        } else {
+            tokens.push(Token::Other);
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Finds the end of a `[[ ... ]]` / `[==[ ... ]==]` block starting at `start`: the
+/// index of its closing bracket and the one right after it, the end of the source if unclosed.
+fn long_bracket(chars: &[char], start: usize) -> Option<(usize, usize)> {
+    if chars.get(start) != Some(&'[') {
+        return None;
+    }
+    let level = chars[start + 1..].iter().take_while(|&&c| c == '=').count();
+    if chars.get(start + level + 1) != Some(&'[') {
+        return None;
+    }
+
+    let mut i = start + level + 2;
+    while i < chars.len() {
+        if chars[i] == ']'
+            && chars[i + 1..].iter().take(level).all(|&c| c == '=')
+            && chars.get(i + level + 1) == Some(&']')
+        {
+            return Some((i, i + level + 2));
+        }
+        i += 1;
+    }
+    Some((chars.len(), chars.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directives_and_plurals() {
+        let manifest = Manifest::parse(
+            r#"fx_version 'cerulean'
+game "gta5"
+
+client_script 'client/main.lua'
+client_scripts {
+    'client/ui.lua', -- the menu
+    "client/*.lua",
+}
+server_scripts({ '@oxmysql/lib/MySQL.lua', 'server/main.lua' })
+dependency 'oxmysql'
+dependencies { 'es_extended', { 'nested' } }
+"#,
+        );
+        assert_eq!(manifest.values("fx_version"), ["cerulean"]);
+        assert_eq!(manifest.values("client_script"), ["client/main.lua", "client/ui.lua", "client/*.lua"]);
+        assert_eq!(manifest.values("server_script"), ["@oxmysql/lib/MySQL.lua", "server/main.lua"]);
+        assert_eq!(manifest.values("dependency"), ["oxmysql", "es_extended", "nested"]);
+        assert!(manifest.has_scripts());
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let manifest = Manifest::parse(
+            "--[[ client_script 'old.lua' ]]
+--[==[ server_script 'gone.lua' ]] still a comment ]==]
+-- shared_script 'commented.lua'
+shared_script 'config.lua' -- 'trailing.lua'",
+        );
+        assert_eq!(manifest.scripts(), ["config.lua"]);
+    }
+
+    #[test]
+    fn long_bracket_strings() {
+        let manifest = Manifest::parse("description [[multi\nline]]\nui_page [==[html/index.html]==]\nfile [=[a]]b]=]");
+        assert_eq!(manifest.values("description"), ["multi\nline"]);
+        assert_eq!(manifest.values("ui_page"), ["html/index.html"]);
+        assert_eq!(manifest.values("file"), ["a]]b"]);
+    }
+
+    #[test]
+    fn escapes_and_unterminated_input() {
+        let manifest = Manifest::parse("author 'O\\'Neil'\nclient_script 'main.lua'\ndescription [[never closed");
+        assert_eq!(manifest.values("author"), ["O'Neil"]);
+        assert_eq!(manifest.scripts(), ["main.lua"]);
+        assert_eq!(manifest.values("description"), ["never closed"]);
+        assert!(!Manifest::parse("--[[ client_script 'x.lua'").has_scripts());
+    }
+}
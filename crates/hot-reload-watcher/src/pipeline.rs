@@ -0,0 +1,1128 @@
+use crate::backend::create_watcher;
+use crate::fx::{FxCommand, FxLink, FxRequest};
+use crate::graph::DependencyGraph;
+use crate::hooks::{load_hooks, spawn_build, BuildDone, Hook};
+use crate::index::{normalize, ContentState};
+use crate::lint::{load_linters, spawn_lint, CompiledLinter};
+use crate::manifest::MANIFEST_FILES;
+use crate::eventlog::now_ms;
+use crate::resync::{adopt_resources, resync, top_level_resources, RescanScope};
+use crate::scanner::{in_ignored_folder, is_watched, VALID_EXTENSIONS};
+use crate::settle::Settler;
+use crate::state::WatcherState;
+use crate::testrunner::spawn_tests;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use hot_reload_common::{ActionState, ActionStatus, ApplyMode, ChangeType, CommandResult, HistoryEvent, PendingChange, ResourceAction, ReloadReason, ReloadStrategy, ResourceChange, ResyncReport, TreeDelta, WatcherMessage};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Raw file events waiting for the pipeline. Once full, new events are dropped
+/// and a full resync is scheduled instead of blocking the notify thread.
+const QUEUE_CAPACITY: usize = 1024;
+const TICK: Duration = Duration::from_millis(50);
+const RESCAN_DELAY: Duration = Duration::from_millis(500);
+const FX_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum WatchSignal {
+    Changed(PathBuf),
+    Rescan { paths: Vec<PathBuf>, reason: String },
+}
+
+/// Sending side of the pipeline queue, owned by the notify callback.
+struct Intake {
+    tx: mpsc::Sender<WatchSignal>,
+    overflowed: Arc<AtomicBool>,
+    state: Arc<WatcherState>,
+    /// Build hooks and linters can be triggered by any file (`.ts`, `.cs`, ...), not only watched ones
+    any_extension: bool,
+    root: PathBuf,
+}
+
+impl Intake {
+    fn push(&self, signal: WatchSignal) {
+        match self.tx.try_send(signal) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.state.stats.dropped_events.fetch_add(1, Ordering::Relaxed);
+                if !self.overflowed.swap(true, Ordering::Relaxed) {
+                    warn!("⚠️ Pipeline queue full, dropping events until the next resync");
+                }
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {}
+        }
+    }
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| VALID_EXTENSIONS.contains(&ext))
+        .unwrap_or(false)
+}
+
+/// Starts the file watcher on the resources folder and the actor turning its
+/// events into reloads. The returned watcher must be kept alive.
+pub fn start(state: Arc<WatcherState>, requests: mpsc::UnboundedReceiver<FxRequest>) -> Result<Box<dyn Watcher + Send>, BoxError> {
+    let (tx, rx) = mpsc::channel::<WatchSignal>(QUEUE_CAPACITY);
+    let overflowed = Arc::new(AtomicBool::new(false));
+    let intake = Intake {
+        tx,
+        overflowed: overflowed.clone(),
+        state: state.clone(),
+        any_extension: !state.config.build_hooks.is_empty() || !state.config.linters.is_empty(),
+        root: normalize(&state.config.watch_root()),
+    };
+
+    let mut watcher = create_watcher(&state.config, move |res: Result<Event, notify::Error>| {
+        match res {
+            // Queue overflow or backend asking for a rescan, the events in between are lost
+            Ok(event) if event.need_rescan() || matches!(event.kind, EventKind::Other) => {
+                warn!("⚠️ File watcher requested a rescan");
+                intake.push(WatchSignal::Rescan {
+                    paths: event.paths,
+                    reason: "file watcher requested a rescan".to_string(),
+                });
+            }
+            Ok(event) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+
+                // Renames carry both the temporary and the final path, every watched one is settled
+                // Only the part below the resources folder counts, the server may live in `C:\temp`
+                let wanted = |path: &&PathBuf| {
+                    is_watched_file(path)
+                        || (intake.any_extension && !in_ignored_folder(path.strip_prefix(&intake.root).unwrap_or(path)))
+                };
+                for path in event.paths.iter().filter(wanted) {
+                    intake.push(WatchSignal::Changed(path.to_path_buf()));
+                }
+            }
+            Err(e) => {
+                error!("❌ File watcher error: {}", e);
+                intake.push(WatchSignal::Rescan {
+                    paths: e.paths.clone(),
+                    reason: e.to_string(),
+                });
+            }
+        }
+    })?;
+
+    watcher.watch(&state.config.watch_root(), RecursiveMode::Recursive)?;
+    info!("✅ Monitoring resources enabled");
+
+    let (build_tx, build_rx) = mpsc::unbounded_channel();
+    let pipeline = Pipeline {
+        settler: Settler::new(&state.config),
+        hooks: load_hooks(&state.config.build_hooks),
+        builds: HashMap::new(),
+        build_tx,
+        tests: HashMap::new(),
+        linters: load_linters(&state.config.linters),
+        lints: HashMap::new(),
+        fx: FxLink::new(state.clone()),
+        rescan: RescanScope::None,
+        rescan_reason: String::new(),
+        rescan_at: Instant::now(),
+        overflowed,
+        touched: BTreeMap::new(),
+        pending: BTreeMap::new(),
+        pending_files: BTreeMap::new(),
+        unconfirmed: HashMap::new(),
+        user: None,
+        state,
+    };
+    tokio::spawn(pipeline.run(rx, build_rx, requests));
+    Ok(watcher)
+}
+
+/// Single owner of everything between a file event and the FXServer command:
+/// events are classified, held back until the files settle, merged per
+/// resource and dispatched one resource at a time in a stable order.
+struct Pipeline {
+    state: Arc<WatcherState>,
+    settler: Settler,
+    hooks: HashMap<String, Hook>,
+    /// Resources being built, with the trigger of the build to run again once it is over
+    builds: HashMap<String, Option<PathBuf>>,
+    build_tx: mpsc::UnboundedSender<BuildDone>,
+    /// Test runs started after a reload, a new reload of the resource cancels its run
+    tests: HashMap<String, JoinHandle<()>>,
+    linters: Vec<CompiledLinter>,
+    /// Running linters per file and linter, a newer change of the file cancels them
+    lints: HashMap<(PathBuf, usize), JoinHandle<()>>,
+    fx: FxLink,
+    rescan: RescanScope,
+    rescan_reason: String,
+    rescan_at: Instant,
+    overflowed: Arc<AtomicBool>,
+    /// Every file settled in the current step per resource, merged changes only keep one
+    touched: BTreeMap<String, BTreeSet<String>>,
+    /// Changes collected in manual mode, waiting for an apply
+    pending: BTreeMap<String, ResourceChange>,
+    /// Every file changed in a pending resource since the last apply
+    pending_files: BTreeMap<String, BTreeSet<String>>,
+    /// Resources FXServer reloaded, snapshotted once no script error followed for a while
+    unconfirmed: HashMap<String, u64>,
+    /// Client whose request is being run, recorded in the history with the reloads it causes
+    user: Option<String>,
+}
+
+impl Pipeline {
+    async fn run(
+        mut self,
+        mut rx: mpsc::Receiver<WatchSignal>,
+        mut build_rx: mpsc::UnboundedReceiver<BuildDone>,
+        mut requests: mpsc::UnboundedReceiver<FxRequest>,
+    ) {
+        let mut tick = tokio::time::interval(TICK);
+
+        loop {
+            tokio::select! {
+                signal = rx.recv() => {
+                    let Some(signal) = signal else {
+                        break;
+                    };
+                    self.classify(signal);
+                }
+
+                Some(done) = build_rx.recv() => {
+                    self.build_finished(done).await;
+                }
+
+                Some(request) = requests.recv() => {
+                    self.user = request.user();
+                    match request {
+                        FxRequest::Command(command) => self.execute(command).await,
+                        FxRequest::Action { resources, action: ResourceAction::Rollback, .. } => {
+                            for resource in resources {
+                                self.rollback(resource, None, false).await;
+                            }
+                        }
+                        FxRequest::Action { resources, action, .. } => self.run_action(resources, action).await,
+                        FxRequest::Resume(changes) => self.reload(changes).await,
+                        FxRequest::Apply { resources, .. } => self.apply_pending(resources).await,
+                        FxRequest::Rollback { resource, snapshot, user } => self.rollback(resource, snapshot, user.is_none()).await,
+                    }
+                    self.user = None;
+                }
+
+                _ = tick.tick() => {
+                    self.state.flush_console_errors();
+                    if self.state.pause_expired() {
+                        let changes = self.state.resume(true);
+                        self.reload(changes).await;
+                    }
+                    self.confirm_reloads().await;
+                    self.fx.keep_alive().await;
+                    if self.fx.take_reconnected() {
+                        self.fx.query_states().await;
+                    }
+                    self.step().await;
+                }
+            }
+        }
+    }
+
+    fn classify(&mut self, signal: WatchSignal) {
+        match signal {
+            WatchSignal::Changed(path) => {
+                // Symlinks are resolved here, the index is never locked around disk lookups
+                let path = normalize(&path);
+                let known = self.state.index.lock().map(|index| index.contains(&path)).unwrap_or(false);
+                self.state.log("classify", format!("{} ({})", path.display(), if known { "known" } else { "new" }));
+                self.settler.touch(path, known);
+            }
+            WatchSignal::Rescan { paths, reason } => {
+                self.state.log("classify", format!("rescan requested: {}", reason));
+                self.schedule_rescan(&paths, reason);
+            }
+        }
+    }
+
+    fn schedule_rescan(&mut self, paths: &[PathBuf], reason: String) {
+        // Overflows come in bursts, wait for things to calm down before walking the tree
+        let paths: Vec<PathBuf> = paths.iter().map(|path| normalize(path)).collect();
+        self.rescan.add_paths(&paths);
+        self.rescan_reason = reason;
+        self.rescan_at = Instant::now() + RESCAN_DELAY;
+    }
+
+    async fn step(&mut self) {
+        if self.overflowed.swap(false, Ordering::Relaxed) {
+            self.state.log("backpressure", "queue was full, events were dropped");
+            self.schedule_rescan(&[], "pipeline queue overflowed".to_string());
+        }
+
+        let mut changes: BTreeMap<String, ResourceChange> = BTreeMap::new();
+
+        if !self.rescan.is_none() && Instant::now() >= self.rescan_at {
+            let scope = std::mem::take(&mut self.rescan);
+            let reason = std::mem::take(&mut self.rescan_reason);
+            for change in self.resync(scope, &reason).await {
+                merge_change(&mut changes, change);
+            }
+        }
+
+        if !self.settler.is_empty() {
+            for (path, change_type) in self.settler.poll() {
+                self.state.log("settle", format!("{} {:?}", path.display(), change_type));
+                let adopted = self.adopt_resources(&path, &change_type).await;
+                if !adopted.is_empty() {
+                    for change in adopted {
+                        merge_change(&mut changes, change);
+                    }
+                    continue;
+                }
+                self.lint(&path, &change_type);
+                if self.hold_for_build(&path) {
+                    continue;
+                }
+                if let Some(change) = self.apply_change(path, change_type) {
+                    self.touched.entry(change.resource_name.clone()).or_default().insert(change.file_path.clone());
+                    merge_change(&mut changes, change);
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+        self.record_changes(&changes);
+        self.reload(changes).await;
+    }
+
+    /// Records what changed on disk in the history, with every file touched in the step.
+    fn record_changes(&self, changes: &BTreeMap<String, ResourceChange>) {
+        for (resource, change) in changes {
+            let files: BTreeSet<&String> = self.touched.get(resource).into_iter().flatten().chain([&change.file_path]).collect();
+            let event = HistoryEvent::Change {
+                change_type: change.change_type.clone(),
+                files: self.relative_files(files.into_iter()),
+            };
+            self.state.record(None, vec![resource.clone()], event);
+        }
+    }
+
+    /// Paths relative to their resource, as the clients show them.
+    fn relative_files<'a>(&self, files: impl Iterator<Item = &'a String>) -> Vec<String> {
+        match self.state.index.lock() {
+            Ok(index) => files
+                .map(|file| index.locate(Path::new(file)).map(|(_, relative)| relative).unwrap_or_else(|| file.clone()))
+                .collect(),
+            Err(_) => files.cloned().collect(),
+        }
+    }
+
+    /// Plans and sends the changes, or keeps them for later while reloads are
+    /// paused or until a client applies them in manual mode.
+    async fn reload(&mut self, mut changes: BTreeMap<String, ResourceChange>) {
+        let touched = std::mem::take(&mut self.touched);
+        if changes.is_empty() || self.state.hold(&changes) {
+            return;
+        }
+        changes.retain(|_, change| !self.state.block(change));
+        if self.state.apply_mode() == ApplyMode::Manual {
+            self.collect(changes, touched);
+            return;
+        }
+        self.send(changes).await;
+    }
+
+    async fn send(&mut self, changes: BTreeMap<String, ResourceChange>) {
+        for change in self.plan(changes) {
+            self.dispatch(change).await;
+        }
+    }
+
+    fn collect(&mut self, changes: BTreeMap<String, ResourceChange>, mut touched: BTreeMap<String, BTreeSet<String>>) {
+        for (resource, change) in changes {
+            info!("🧺 {} changed, waiting for an apply", resource);
+            let files = self.pending_files.entry(resource.clone()).or_default();
+            files.insert(change.file_path.clone());
+            files.extend(touched.remove(&resource).unwrap_or_default());
+            self.state.log("collect", format!("{} ({} file(s))", resource, files.len()));
+            merge_change(&mut self.pending, change);
+        }
+        self.publish_pending();
+    }
+
+    /// Sends the preview of the collected changes: files, strategy and what restarts with them.
+    fn publish_pending(&self) {
+        let preview = self
+            .pending
+            .iter()
+            .map(|(resource, change)| {
+                let planned = self.expand(BTreeMap::from([(resource.clone(), change.clone())]));
+                let files = self.relative_files(self.pending_files.get(resource).into_iter().flatten());
+                PendingChange {
+                    resource: resource.clone(),
+                    change_type: change.change_type.clone(),
+                    files,
+                    strategy: planned
+                        .iter()
+                        .find(|change| change.resource_name == *resource)
+                        .map(|change| change.strategy.clone())
+                        .unwrap_or(ReloadStrategy::Never),
+                    restarts: planned
+                        .into_iter()
+                        .map(|change| change.resource_name)
+                        .filter(|name| name != resource)
+                        .collect(),
+                }
+            })
+            .collect();
+        self.state.set_pending(preview);
+    }
+
+    /// Reloads the changes collected for these resources, together so the cascade runs once.
+    async fn apply_pending(&mut self, resources: Vec<String>) {
+        let mut changes = BTreeMap::new();
+        for resource in resources {
+            self.pending_files.remove(&resource);
+            if let Some(change) = self.pending.remove(&resource) {
+                changes.insert(resource, change);
+            }
+        }
+        if changes.is_empty() {
+            return;
+        }
+        info!("🧺 Applying collected changes: {}", changes.keys().cloned().collect::<Vec<_>>().join(", "));
+        self.state.log("apply", changes.keys().cloned().collect::<Vec<_>>().join(", "));
+        self.publish_pending();
+        self.send(changes).await;
+    }
+
+    /// Whether a linter covers one of the indexed files of the resource.
+    fn is_linted(&self, resource: &str) -> bool {
+        !self.linters.is_empty()
+            && self.state.index.lock().is_ok_and(|index| {
+                index
+                    .resource(resource)
+                    .is_some_and(|indexed| indexed.files.keys().any(|file| self.linters.iter().any(|linter| linter.applies_to(file))))
+            })
+    }
+
+    /// Runs the linters applying to `path` without holding the reload back.
+    fn lint(&mut self, path: &Path, change_type: &ChangeType) {
+        if self.linters.is_empty() {
+            return;
+        }
+        let located = self.state.index.lock().ok().and_then(|index| {
+            let (resource, relative_path) = index.locate(path)?;
+            let root = index.resource(&resource)?.root.clone();
+            Some((resource, relative_path, root))
+        });
+        let Some((resource, relative_path, root)) = located else {
+            return;
+        };
+
+        if matches!(change_type, ChangeType::FileRemoved) {
+            self.state.clear_lint(&resource, &relative_path.replace('\\', "/"));
+            return;
+        }
+
+        self.lints.retain(|_, handle| !handle.is_finished());
+        for (i, linter) in self.linters.iter().enumerate() {
+            if !linter.applies_to(&relative_path) {
+                continue;
+            }
+            if let Some(previous) = self.lints.remove(&(path.to_path_buf(), i)) {
+                previous.abort();
+            }
+            let handle = spawn_lint(self.state.clone(), resource.clone(), root.clone(), relative_path.clone(), linter.config.clone());
+            self.lints.insert((path.to_path_buf(), i), handle);
+        }
+    }
+
+    /// Starts the build when `path` is a hook source. Outputs of a running
+    /// build are held back too, the resource is reloaded once it succeeds.
+    fn hold_for_build(&mut self, path: &Path) -> bool {
+        if self.hooks.is_empty() {
+            return false;
+        }
+        let Some((resource, relative_path)) = self.state.index.lock().ok().and_then(|index| index.locate(path)) else {
+            return false;
+        };
+        let Some(hook) = self.hooks.get(&resource) else {
+            return false;
+        };
+
+        if hook.is_source(&relative_path) {
+            self.request_build(resource, path.to_path_buf());
+            return true;
+        }
+        if hook.is_output(&relative_path) && self.builds.contains_key(&resource) {
+            self.state.log("build", format!("{} held until the build of {} is over", relative_path, resource));
+            return true;
+        }
+        false
+    }
+
+    fn request_build(&mut self, resource: String, trigger: PathBuf) {
+        if let Some(rerun) = self.builds.get_mut(&resource) {
+            self.state.log("build", format!("{} changed during the build of {}, building again after", trigger.display(), resource));
+            *rerun = Some(trigger);
+            return;
+        }
+
+        let root = self.state.index.lock().ok().and_then(|index| index.resource(&resource).map(|r| r.root.clone()));
+        let (Some(root), Some(hook)) = (root, self.hooks.get(&resource)) else {
+            return;
+        };
+        self.builds.insert(resource.clone(), None);
+        spawn_build(self.state.clone(), resource, root, hook.config.clone(), trigger, self.build_tx.clone());
+    }
+
+    async fn build_finished(&mut self, done: BuildDone) {
+        if let Some(Some(trigger)) = self.builds.remove(&done.resource) {
+            self.request_build(done.resource, trigger);
+            return;
+        }
+        if !done.success {
+            return;
+        }
+
+        // The outputs are indexed now, their own events will be suppressed as unchanged
+        let blocking_state = self.state.clone();
+        let resource = done.resource.clone();
+        let diff = tokio::task::spawn_blocking(move || {
+            let mut index = blocking_state.index.lock().ok()?;
+            let diff = index.rescan_resource(&resource);
+            blocking_state.stats.set_indexed_files(index.file_count());
+            Some(diff)
+        })
+        .await
+        .ok()
+        .flatten();
+
+        let Some(diff) = diff else {
+            return;
+        };
+        if diff.is_empty() {
+            info!("⏭️ Build of {} changed no watched file, skipping reload", done.resource);
+            self.state.log("build", format!("{} output unchanged", done.resource));
+            return;
+        }
+        if !diff.added.is_empty() || !diff.removed.is_empty() {
+            self.state.broadcast(WatcherMessage::TreeDelta(diff.tree_delta(&done.resource)));
+        }
+
+        let mut changes = BTreeMap::new();
+        changes.insert(done.resource.clone(), ResourceChange {
+            resource_name: done.resource,
+            change_type: diff.change_type(),
+            file_path: done.trigger.to_string_lossy().into_owned(),
+            reason: ReloadReason::FileChanged,
+            strategy: ReloadStrategy::Auto,
+        });
+        self.record_changes(&changes);
+        self.reload(changes).await;
+    }
+
+    /// Adds the resources importing the changed files and the dependents that
+    /// have to restart after them, then sorts everything so a resource always
+    /// comes after its dependencies.
+    fn plan(&self, changes: BTreeMap<String, ResourceChange>) -> Vec<ResourceChange> {
+        for name in changes.keys().filter(|name| self.state.strategy(name) == ReloadStrategy::Never) {
+            info!("⏸️ {} is never reloaded automatically, skipping", name);
+            self.state.log("skip", format!("{} never reloads automatically", name));
+        }
+
+        let planned = self.expand(changes);
+        for change in &planned {
+            match &change.reason {
+                ReloadReason::Import { resource, file } => {
+                    info!("📎 {} loads @{}/{}, reloading it too", change.resource_name, resource, file);
+                    self.state.log("import", format!("{} loads @{}/{}", change.resource_name, resource, file));
+                }
+                ReloadReason::Dependency { resource } => {
+                    info!("⛓️ {} depends on {}, restarting it too", change.resource_name, resource);
+                    self.state.log("cascade", format!("{} after {}", change.resource_name, resource));
+                }
+                _ => {}
+            }
+        }
+        let order: Vec<&str> = planned.iter().map(|change| change.resource_name.as_str()).collect();
+        self.state.log("order", order.join(" -> "));
+        planned
+    }
+
+    /// What `plan` sends for the changes, without logging anything.
+    fn expand(&self, mut changes: BTreeMap<String, ResourceChange>) -> Vec<ResourceChange> {
+        let (Ok(index), Ok(graph)) = (self.state.index.lock(), self.state.graph.lock()) else {
+            return changes.into_values().collect();
+        };
+
+        let changed_files: Vec<(String, String, String)> = changes
+            .values()
+            .filter_map(|change| {
+                let (resource, relative_path) = index.locate(Path::new(&change.file_path))?;
+                Some((resource, relative_path, change.file_path.clone()))
+            })
+            .collect();
+        for (resource, relative_path, file_path) in changed_files {
+            for importer in graph.importers_of(&resource, &relative_path) {
+                if changes.contains_key(&importer) {
+                    continue;
+                }
+                changes.insert(importer.clone(), ResourceChange {
+                    resource_name: importer,
+                    change_type: ChangeType::FileModified,
+                    file_path: file_path.clone(),
+                    reason: ReloadReason::Import {
+                        resource: resource.clone(),
+                        file: relative_path.clone(),
+                    },
+                    strategy: ReloadStrategy::Auto,
+                });
+            }
+        }
+
+        changes.retain(|name, _| self.state.strategy(name) != ReloadStrategy::Never && !self.state.is_tripped(name));
+
+        let reloaded: BTreeSet<String> = changes.keys().cloned().collect();
+        let no_cascade = &self.state.config.no_cascade;
+        let skip = |name: &str| {
+            no_cascade.iter().any(|skipped| skipped == name)
+                || self.state.strategy(name) == ReloadStrategy::Never
+                || self.state.is_tripped(name)
+        };
+        for (dependent, cause) in graph.cascade(&reloaded, skip) {
+            let file_path = changes[&cause].file_path.clone();
+            changes.insert(dependent.clone(), ResourceChange {
+                resource_name: dependent,
+                change_type: ChangeType::FileModified,
+                file_path,
+                reason: ReloadReason::Dependency { resource: cause },
+                strategy: ReloadStrategy::Auto,
+            });
+        }
+
+        graph
+            .order(changes.keys().cloned())
+            .into_iter()
+            .filter_map(|name| changes.remove(&name))
+            .map(|mut change| {
+                change.strategy = self.state.strategy(&change.resource_name).resolve(&change.change_type);
+                change
+            })
+            .collect()
+    }
+
+    /// Updates the index with a settled file and returns the reload it calls for.
+    fn apply_change(&mut self, path: PathBuf, change_type: ChangeType) -> Option<ResourceChange> {
+        let state = &self.state;
+        let (resource_name, relative_path, content_state) = {
+            let mut index = state.index.lock().ok()?;
+            let (resource_name, relative_path) = index.locate(&path)?;
+            if !is_watched(Path::new(&relative_path)) {
+                return None;
+            }
+
+            // Editors and git often rewrite identical bytes, only real content changes go through
+            let content_state = match change_type {
+                ChangeType::FileRemoved => {
+                    index.remove(&path);
+                    ContentState::Changed
+                }
+                _ => index.update(&path),
+            };
+            state.stats.set_indexed_files(index.file_count());
+
+            if MANIFEST_FILES.contains(&relative_path.as_str()) && content_state != ContentState::Unchanged {
+                let root = index.resource(&resource_name).map(|r| r.root.clone());
+                let updated = match (root, state.graph.lock()) {
+                    (Some(root), Ok(mut graph)) => graph.update_resource(&resource_name, &root),
+                    _ => false,
+                };
+                if updated {
+                    info!("🕸️ Dependencies of {} changed", resource_name);
+                    state.log("graph", format!("dependencies of {} changed", resource_name));
+                    state.graph_changed();
+                }
+            }
+            (resource_name, relative_path, content_state)
+        };
+
+        if content_state == ContentState::Unchanged {
+            state.stats.suppressed_events.fetch_add(1, Ordering::Relaxed);
+            state.log("suppress", format!("{} content unchanged", path.display()));
+            info!("⏭️ Content unchanged, skipping: {}", path.display());
+            return None;
+        }
+
+        let delta = match change_type {
+            ChangeType::FileAdded => Some(TreeDelta {
+                resource_name: resource_name.clone(),
+                added: vec![relative_path],
+                removed: Vec::new(),
+            }),
+            ChangeType::FileRemoved => Some(TreeDelta {
+                resource_name: resource_name.clone(),
+                added: Vec::new(),
+                removed: vec![relative_path],
+            }),
+            ChangeType::FileModified => None,
+        };
+        if let Some(delta) = delta {
+            state.broadcast(WatcherMessage::TreeDelta(delta));
+        }
+
+        Some(ResourceChange {
+            resource_name,
+            change_type,
+            file_path: path.to_string_lossy().into_owned(),
+            reason: ReloadReason::FileChanged,
+            strategy: ReloadStrategy::Auto,
+        })
+    }
+
+    /// Indexes the resources created since the scan when `path` is a manifest
+    /// or a file no known resource owns, and returns their reloads.
+    async fn adopt_resources(&mut self, path: &Path, change_type: &ChangeType) -> Vec<ResourceChange> {
+        if matches!(change_type, ChangeType::FileRemoved) {
+            return Vec::new();
+        }
+        let is_manifest = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| MANIFEST_FILES.contains(&name));
+        let unplaced = match self.state.index.lock() {
+            Ok(index) => match index.locate(path) {
+                Some((_, relative_path)) => is_manifest && !MANIFEST_FILES.contains(&relative_path.as_str()),
+                None => true,
+            },
+            Err(_) => false,
+        };
+        if !unplaced {
+            return Vec::new();
+        }
+
+        let blocking_state = self.state.clone();
+        let trigger = path.to_path_buf();
+        let adopted = tokio::task::spawn_blocking(move || {
+            let root = normalize(&blocking_state.config.watch_root());
+            let found = top_level_resources(&root, &trigger);
+            let mut index = blocking_state.index.lock().ok()?;
+            let diffs = adopt_resources(&mut index, found);
+            if diffs.is_empty() {
+                return None;
+            }
+            blocking_state.stats.set_indexed_files(index.file_count());
+            let graph = DependencyGraph::build(&index);
+            if let Ok(mut current) = blocking_state.graph.lock() {
+                *current = graph;
+            }
+            Some(diffs)
+        })
+        .await
+        .ok()
+        .flatten();
+
+        let Some(diffs) = adopted else {
+            return Vec::new();
+        };
+        self.state.graph_changed();
+        let mut changes = Vec::with_capacity(diffs.len());
+        for (resource_name, diff) in diffs {
+            self.state.log("adopt", format!("{} ({} file(s) added)", resource_name, diff.added.len()));
+            self.state.broadcast(WatcherMessage::TreeDelta(diff.tree_delta(&resource_name)));
+            changes.push(ResourceChange {
+                resource_name,
+                change_type: diff.change_type(),
+                file_path: path.to_string_lossy().into_owned(),
+                reason: ReloadReason::FileChanged,
+                strategy: ReloadStrategy::Auto,
+            });
+        }
+        changes
+    }
+
+    async fn resync(&mut self, scope: RescanScope, reason: &str) -> Vec<ResourceChange> {
+        info!("🔄 Resync started: {}", reason);
+        self.state.log("resync", format!("started: {}", reason));
+        let blocking_state = self.state.clone();
+        let diffs = tokio::task::spawn_blocking(move || {
+            let root = blocking_state.config.watch_root();
+            let mut index = blocking_state.index.lock().map_err(|e| e.to_string())?;
+            let diffs = resync(&mut index, &root, scope);
+            let roots: Vec<PathBuf> = diffs
+                .iter()
+                .map(|(name, _)| index.resource(name).map(|r| r.root.clone()).unwrap_or_else(|| root.join(name)))
+                .collect();
+            blocking_state.stats.set_indexed_files(index.file_count());
+            if !diffs.is_empty() {
+                let graph = DependencyGraph::build(&index);
+                if let Ok(mut current) = blocking_state.graph.lock() {
+                    *current = graph;
+                }
+            }
+            Ok::<_, String>((diffs, roots))
+        })
+        .await;
+
+        let (diffs, roots) = match diffs {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                error!("❌ Resync failed: {}", e);
+                return Vec::new();
+            }
+            Err(e) => {
+                error!("❌ Resync failed: {}", e);
+                return Vec::new();
+            }
+        };
+
+        if !diffs.is_empty() {
+            self.state.graph_changed();
+        }
+
+        let mut changed_files = 0;
+        let mut changes = Vec::with_capacity(diffs.len());
+        for ((resource_name, diff), root) in diffs.iter().zip(roots) {
+            changed_files += diff.added.len() + diff.removed.len() + diff.modified.len();
+            if !diff.added.is_empty() || !diff.removed.is_empty() {
+                self.state.broadcast(WatcherMessage::TreeDelta(diff.tree_delta(resource_name)));
+            }
+
+            let file = diff.added.iter().chain(&diff.modified).chain(&diff.removed).next();
+            changes.push(ResourceChange {
+                resource_name: resource_name.clone(),
+                change_type: diff.change_type(),
+                file_path: file.map(|file| root.join(file)).unwrap_or(root).to_string_lossy().into_owned(),
+                reason: ReloadReason::FileChanged,
+                strategy: ReloadStrategy::Auto,
+            });
+        }
+
+        info!("✅ Resync finished: {} resource(s), {} file(s) changed", diffs.len(), changed_files);
+        self.state.log("resync", format!("finished: {} resource(s), {} file(s) changed", diffs.len(), changed_files));
+        self.state.broadcast(WatcherMessage::Resync(ResyncReport {
+            reason: reason.to_string(),
+            resources: diffs.into_iter().map(|(name, _)| name).collect(),
+            changed_files,
+        }));
+        changes
+    }
+
+    fn run_tests(&mut self, resource: &str) {
+        let Some(runner) = self.state.config.test_runners.get(resource) else {
+            return;
+        };
+        let root = self.state.index.lock().ok().and_then(|index| index.resource(resource).map(|r| r.root.clone()));
+        let Some(root) = root else {
+            return;
+        };
+
+        if let Some(previous) = self.tests.remove(resource) {
+            if !previous.is_finished() {
+                self.state.log("test", format!("{} reloaded again, previous run cancelled", resource));
+                previous.abort();
+            }
+        }
+        let handle = spawn_tests(self.state.clone(), resource.to_string(), root, runner.clone());
+        self.tests.insert(resource.to_string(), handle);
+    }
+
+    async fn dispatch(&mut self, change: ResourceChange) {
+        info!("✨ Change detected: {:?}", change);
+        self.state.stats.processed_events.fetch_add(1, Ordering::Relaxed);
+        self.state.log("dispatch", format!("{} {:?} ({:?})", change.resource_name, change.change_type, change.reason));
+        self.state.broadcast(WatcherMessage::Reload(change.clone()));
+
+        self.action_status(&change, ActionState::Running, None);
+        let automatic = !matches!(change.reason, ReloadReason::Manual { .. });
+        if automatic {
+            self.state.reload_started(&change.resource_name);
+        }
+
+        let (message, failed) = match tokio::time::timeout(FX_TIMEOUT, self.fx.send_change(&change)).await {
+            Ok(Some(response_text)) => {
+                info!("FXserver response: {}", response_text);
+                self.state.log("response", format!("{}: {}", change.resource_name, response_text));
+                // `missing <name>` or `error <name>: ...` when FXServer could not run the change
+                let refused = response_text.starts_with("missing ") || response_text.starts_with("error ");
+                let outcome = match refused {
+                    true => ActionState::Failed,
+                    false => ActionState::Succeeded,
+                };
+                self.action_status(&change, outcome, Some(response_text.clone()));
+                self.state.fx_output(response_text.clone());
+                if change.reason != (ReloadReason::Manual { action: ResourceAction::Stop }) {
+                    self.run_tests(&change.resource_name);
+                }
+                self.fx.query_states().await;
+                (response_text, refused)
+            }
+            Ok(None) => {
+                self.state.log("response", format!("{}: no answer from FXserver", change.resource_name));
+                self.action_status(&change, ActionState::Failed, Some("no answer from FXserver".to_string()));
+                ("no answer from FXserver".to_string(), true)
+            }
+            Err(_) => {
+                warn!("⚠️ FXserver did not answer within {:?}", FX_TIMEOUT);
+                self.state.log("response", format!("{}: timed out", change.resource_name));
+                self.action_status(&change, ActionState::Failed, Some("timed out".to_string()));
+                self.fx.reset();
+                ("timed out".to_string(), true)
+            }
+        };
+        let failure = failed.then(|| message.clone());
+        self.state.record(self.user.as_deref(), vec![change.resource_name.clone()], HistoryEvent::Reload {
+            reason: change.reason.clone(),
+            strategy: change.strategy.clone(),
+            success: !failed,
+            message: Some(message),
+        });
+        match (automatic, failure) {
+            (true, Some(error)) => self.state.reload_failed(&change.resource_name, error),
+            (true, None) if self.state.config.snapshots.enabled => {
+                self.unconfirmed.insert(change.resource_name.clone(), now_ms());
+            }
+            (false, None) if change.reason != (ReloadReason::Manual { action: ResourceAction::Stop }) && !self.is_linted(&change.resource_name) => {
+                self.state.manual_reload_succeeded(&change.resource_name);
+            }
+            _ => {}
+        }
+    }
+
+    /// Snapshots the resources reloaded without a failure during the grace period of the breaker.
+    async fn confirm_reloads(&mut self) {
+        let grace = self.state.config.circuit_breaker.error_grace_ms;
+        let now = now_ms();
+        let confirmed: Vec<(String, u64)> = self
+            .unconfirmed
+            .iter()
+            .filter(|(_, at)| now.saturating_sub(**at) > grace)
+            .map(|(resource, at)| (resource.clone(), *at))
+            .collect();
+        for (resource, at) in confirmed {
+            self.unconfirmed.remove(&resource);
+            if self.state.failed_since(&resource, at) {
+                continue;
+            }
+            self.snapshot(resource).await;
+        }
+    }
+
+    async fn snapshot(&self, resource: String) {
+        let blocking_state = self.state.clone();
+        let taken = tokio::task::spawn_blocking(move || {
+            let (root, files) = {
+                let index = blocking_state.index.lock().map_err(|e| e.to_string())?;
+                let indexed = index.resource(&resource).ok_or("resource is not indexed")?;
+                (indexed.root.clone(), indexed.files.keys().cloned().collect::<Vec<_>>())
+            };
+            let taken = blocking_state.snapshots.take(&resource, &root, &files).map_err(|e| e.to_string())?;
+            Ok::<_, String>((resource, taken))
+        })
+        .await;
+
+        match taken {
+            Ok(Ok((resource, Some(info)))) => {
+                info!("📸 Snapshot of {} taken: {} file(s)", resource, info.files);
+                self.state.log("snapshot", format!("{} #{} ({} file(s), {} bytes)", resource, info.id, info.files, info.bytes));
+                self.state.broadcast(WatcherMessage::Snapshots(self.state.snapshots.list(&resource)));
+            }
+            Ok(Ok((resource, None))) => self.state.log("snapshot", format!("{} unchanged since its last snapshot", resource)),
+            Ok(Err(e)) => error!("❌ Snapshot failed: {}", e),
+            Err(e) => error!("❌ Snapshot failed: {}", e),
+        }
+    }
+
+    /// Restores a snapshot of the resource and reloads it. An automatic rollback
+    /// follows a failed reload and is skipped when the files match the snapshot.
+    async fn rollback(&mut self, resource: String, snapshot: Option<u64>, automatic: bool) {
+        let status = |state: ActionState, message: Option<String>| {
+            WatcherMessage::ActionStatus(ActionStatus {
+                resource: resource.clone(),
+                action: ResourceAction::Rollback,
+                state,
+                message,
+            })
+        };
+        if !automatic {
+            self.state.broadcast(status(ActionState::Queued, None));
+        }
+        self.unconfirmed.remove(&resource);
+
+        let blocking_state = self.state.clone();
+        let name = resource.clone();
+        let restored = tokio::task::spawn_blocking(move || {
+            let mut index = blocking_state.index.lock().map_err(|e| e.to_string())?;
+            let indexed = index.resource(&name).ok_or("resource is not indexed")?;
+            let (root, files) = (indexed.root.clone(), indexed.files.keys().cloned().collect::<Vec<_>>());
+            let restored = blocking_state.snapshots.restore(&name, snapshot, &root, &files).map_err(|e| e.to_string())?;
+            // The index takes the restored files now, their own events will be suppressed as unchanged
+            let diff = index.rescan_resource(&name);
+            blocking_state.stats.set_indexed_files(index.file_count());
+            let graph_changed = !diff.is_empty() && blocking_state.graph.lock().is_ok_and(|mut graph| graph.update_resource(&name, &root));
+            Ok::<_, String>((restored, diff, root, graph_changed))
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|restored| restored);
+
+        let (restored, diff, root, graph_changed) = match restored {
+            Ok(restored) => restored,
+            Err(e) => {
+                error!("❌ Rollback of {} failed: {}", resource, e);
+                self.state.log("rollback", format!("{}: {}", resource, e));
+                self.state.broadcast(status(ActionState::Failed, Some(e)));
+                return;
+            }
+        };
+        if let Some(saved) = restored.saved {
+            self.state.log("snapshot", format!("{} #{} saved before the rollback", resource, saved));
+            self.state.broadcast(WatcherMessage::Snapshots(self.state.snapshots.list(&resource)));
+        }
+        if graph_changed {
+            self.state.graph_changed();
+        }
+        if !diff.added.is_empty() || !diff.removed.is_empty() {
+            self.state.broadcast(WatcherMessage::TreeDelta(diff.tree_delta(&resource)));
+        }
+        if !restored.failed.is_empty() {
+            // Half rolled back, the breaker stays tripped with the changes it holds
+            let message = format!(
+                "partially rolled back to snapshot #{} ({} written, {} removed), failed: {}",
+                restored.id,
+                restored.written,
+                restored.removed,
+                restored.failed.join(", ")
+            );
+            error!("❌ Rollback of {} failed: {}", resource, message);
+            self.state.log("rollback", format!("{}: {}", resource, message));
+            self.state.broadcast(status(ActionState::Failed, Some(message)));
+            return;
+        }
+        if automatic && diff.is_empty() {
+            self.state.log("rollback", format!("{} already matches snapshot #{}", resource, restored.id));
+            return;
+        }
+        // Back to files that reloaded fine, the breaker can close
+        self.state.rolled_back(&resource);
+
+        info!("⏪ {} rolled back to snapshot #{}: {} file(s) written, {} removed", resource, restored.id, restored.written, restored.removed);
+        self.state.log("rollback", format!("{} to #{} ({} written, {} removed)", resource, restored.id, restored.written, restored.removed));
+
+        let change_type = diff.change_type();
+        let strategy = self.explicit_strategy(&resource, &change_type);
+        self.dispatch(ResourceChange {
+            resource_name: resource,
+            change_type,
+            file_path: root.to_string_lossy().into_owned(),
+            reason: ReloadReason::Manual { action: ResourceAction::Rollback },
+            strategy,
+        })
+        .await;
+    }
+
+    /// Strategy of a reload asked for explicitly, a resource that never reloads on its own still gets ensured.
+    fn explicit_strategy(&self, resource: &str, change_type: &ChangeType) -> ReloadStrategy {
+        match self.state.strategy(resource) {
+            ReloadStrategy::Never => ReloadStrategy::Ensure,
+            strategy => strategy.resolve(change_type),
+        }
+    }
+
+    /// Tells the clients how a manual action is going, reloads from file changes have no status.
+    fn action_status(&self, change: &ResourceChange, state: ActionState, message: Option<String>) {
+        if let ReloadReason::Manual { action } = change.reason {
+            self.state.broadcast(WatcherMessage::ActionStatus(ActionStatus {
+                resource: change.resource_name.clone(),
+                action,
+                state,
+                message,
+            }));
+        }
+    }
+
+    /// Runs an action asked from the UI on each resource, dependencies first.
+    async fn run_action(&mut self, resources: Vec<String>, action: ResourceAction) {
+        let order = match self.state.graph.lock() {
+            Ok(graph) => graph.order(resources),
+            Err(_) => return,
+        };
+        self.state.log("action", format!("{}: {}", action.name(), order.join(" -> ")));
+
+        let changes: Vec<ResourceChange> = order
+            .into_iter()
+            .map(|resource| {
+                let strategy = match action {
+                    ResourceAction::Start => ReloadStrategy::Custom {
+                        commands: vec!["start {resource}".to_string()],
+                    },
+                    ResourceAction::Stop => ReloadStrategy::Custom {
+                        commands: vec!["stop {resource}".to_string()],
+                    },
+                    ResourceAction::Restart => ReloadStrategy::Restart,
+                    ResourceAction::RefreshEnsure => ReloadStrategy::RefreshEnsure,
+                    // Rollbacks are sent to `rollback` and never get here
+                    ResourceAction::Reload | ResourceAction::Rollback => self.explicit_strategy(&resource, &ChangeType::FileModified),
+                };
+                ResourceChange {
+                    resource_name: resource,
+                    change_type: ChangeType::FileModified,
+                    file_path: String::new(),
+                    reason: ReloadReason::Manual { action },
+                    strategy,
+                }
+            })
+            .collect();
+
+        for change in &changes {
+            self.action_status(change, ActionState::Queued, None);
+        }
+        for change in changes {
+            self.dispatch(change).await;
+        }
+    }
+
+    /// Runs a client's console command through the same link as the reloads.
+    async fn execute(&mut self, command: FxCommand) {
+        info!("⌨️ Running console command: {}", command.command);
+        self.state.log("command", command.command.clone());
+
+        let (output, error) = match tokio::time::timeout(FX_TIMEOUT, self.fx.send_command(&command.command)).await {
+            Ok(Some(reply)) => (reply.output, reply.error),
+            Ok(None) => (Vec::new(), Some("no answer from FXserver".to_string())),
+            Err(_) => {
+                warn!("⚠️ FXserver did not answer within {:?}", FX_TIMEOUT);
+                self.fx.reset();
+                (Vec::new(), Some("timed out".to_string()))
+            }
+        };
+        if let Some(error) = &error {
+            self.state.log("command", format!("{}: {}", command.command, error));
+        }
+        self.state.record(Some(&command.user), Vec::new(), HistoryEvent::Command {
+            command: command.command.clone(),
+            error: error.clone(),
+        });
+        let _ = command.reply.send(WatcherMessage::CommandResult(CommandResult {
+            id: command.id,
+            command: command.command,
+            output,
+            error,
+        }));
+    }
+}
+
+/// Keeps one change per resource, adds and removes win over plain edits
+/// because they need a `refresh` first.
+pub(crate) fn merge_change(changes: &mut BTreeMap<String, ResourceChange>, change: ResourceChange) {
+    match changes.get(&change.resource_name) {
+        Some(existing) if !matches!(existing.change_type, ChangeType::FileModified) => {}
+        _ => {
+            changes.insert(change.resource_name.clone(), change);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::index::{ResourceDiff, ResourceIndex};
+use crate::scanner::{discover_resources, walk_for_resources};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Which part of the tree has to be compared again with the index.
+#[derive(Debug, Clone, Default)]
+pub enum RescanScope {
+    /// Nothing requested yet
+    #[default]
+    None,
+    /// Only the resources containing these paths
+    Paths(BTreeSet<PathBuf>),
+    /// The whole resources folder, used when the backend lost track of what changed
+    All,
+}
+
+impl RescanScope {
+    pub fn is_none(&self) -> bool {
+        matches!(self, RescanScope::None)
+    }
+
+    pub fn add_paths(&mut self, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            *self = RescanScope::All;
+            return;
+        }
+        match self {
+            RescanScope::All => {}
+            RescanScope::Paths(known) => known.extend(paths.iter().cloned()),
+            RescanScope::None => *self = RescanScope::Paths(paths.iter().cloned().collect()),
+        }
+    }
+}
+
+/// Brings the index back in line with the disk after events were dropped.
+///
+/// A full rescan also picks up resources that appeared or disappeared; a
+/// scoped one only compares the resources owning the given paths. Returns
+/// the per-resource differences that still have to be reloaded.
+pub fn resync(index: &mut ResourceIndex, root: &Path, scope: RescanScope) -> Vec<(String, ResourceDiff)> {
+    let mut diffs = Vec::new();
+    let names: BTreeSet<String> = match scope {
+        RescanScope::None => return diffs,
+        RescanScope::Paths(paths) => paths
+            .iter()
+            .filter_map(|path| index.locate(path).map(|(name, _)| name))
+            .collect(),
+        RescanScope::All => {
+            let (on_disk, _) = discover_resources(root);
+
+            for (name, path) in &on_disk {
+                if index.resource(name).is_none() {
+                    info!("📦 New resource found during resync: {}", name);
+                    index.insert_resource(name.clone(), path);
+                }
+            }
+
+            for name in index.resource_names() {
+                if on_disk.iter().any(|(found, _)| *found == name) {
+                    continue;
+                }
+                warn!("🗑️ Resource vanished during resync: {}", name);
+                if let Some(resource) = index.remove_resource(&name) {
+                    let diff = ResourceDiff {
+                        removed: resource.files.into_keys().collect(),
+                        ..Default::default()
+                    };
+                    if !diff.is_empty() {
+                        diffs.push((name, diff));
+                    }
+                }
+            }
+
+            index.resource_names().into_iter().collect()
+        }
+    };
+
+    for name in names {
+        let diff = index.rescan_resource(&name);
+        if !diff.is_empty() {
+            diffs.push((name, diff));
+        }
+    }
+    diffs
+}
+
+/// Resources in the top-level folder of `path` (`[jobs]`, or the resource
+/// itself), to find one that just got its `fxmanifest.lua`. Walks the disk,
+/// call it without holding the index.
+pub fn top_level_resources(root: &Path, path: &Path) -> Vec<(String, PathBuf)> {
+    let Some(top_level) = path.strip_prefix(root).ok().and_then(|relative| relative.components().next()) else {
+        return Vec::new();
+    };
+    let dir = root.join(top_level);
+    if !dir.is_dir() {
+        return Vec::new();
+    }
+    walk_for_resources(&dir).0
+}
+
+/// Indexes the resources of `found` the index does not know yet. Their files
+/// come back as added, and a resource they are nested in loses the files now theirs.
+pub fn adopt_resources(index: &mut ResourceIndex, found: Vec<(String, PathBuf)>) -> Vec<(String, ResourceDiff)> {
+    let mut names = BTreeSet::new();
+    for (name, path) in found {
+        // A resource created meanwhile with the same name keeps it, like in the first scan
+        if index.resource(&name).is_some() {
+            continue;
+        }
+        info!("📦 New resource found: {}", name);
+        index.insert_resource(name.clone(), &path);
+        let root = index.resource(&name).map(|resource| resource.root.clone()).unwrap_or(path);
+        names.extend(
+            index
+                .resource_names()
+                .into_iter()
+                .filter(|owner| *owner != name && index.resource(owner).is_some_and(|resource| root.starts_with(&resource.root))),
+        );
+        names.insert(name);
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            let diff = index.rescan_resource(&name);
+            (name, diff)
+        })
+        .filter(|(_, diff)| !diff.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::build_index;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn resources_created_after_the_scan_are_adopted() {
+        let root = crate::index::normalize(&std::env::temp_dir().join(format!("hot-reload-resync-{}", std::process::id())));
+        write(&root.join("[core]/base/fxmanifest.lua"), "client_script 'main.lua'");
+        write(&root.join("[core]/base/main.lua"), "print(1)");
+        write(&root.join("[core]/base/addon/main.lua"), "print(3)");
+        let mut index = build_index(&root).unwrap().index;
+
+        write(&root.join("[core]/fresh/client/main.lua"), "print(2)");
+        let found = top_level_resources(&root, &root.join("[core]/fresh/client/main.lua"));
+        assert!(adopt_resources(&mut index, found).is_empty());
+
+        write(&root.join("[core]/fresh/fxmanifest.lua"), "client_script 'client/main.lua'");
+        write(&root.join("[core]/base/addon/fxmanifest.lua"), "server_script 'main.lua'");
+        let found = top_level_resources(&root, &root.join("[core]/fresh/fxmanifest.lua"));
+        let mut diffs = adopt_resources(&mut index, found);
+        for (_, diff) in &mut diffs {
+            diff.added.sort();
+        }
+
+        let names: Vec<&str> = diffs.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["addon", "base", "fresh"]);
+        assert_eq!(diffs[1].1.removed, [Path::new("addon").join("main.lua").to_string_lossy().to_string()]);
+        assert_eq!(diffs[2].1.added, [Path::new("client").join("main.lua").to_string_lossy().to_string(), "fxmanifest.lua".to_string()]);
+        assert_eq!(index.locate(&root.join("[core]/fresh/client/main.lua")).map(|(name, _)| name), Some("fresh".to_string()));
+        let found = top_level_resources(&root, &root.join("[core]/fresh/fxmanifest.lua"));
+        assert!(adopt_resources(&mut index, found).is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+use crate::graph::DependencyGraph;
+use crate::index::{read_entry, ResourceIndex};
+use crate::manifest::Manifest;
+use rayon::prelude::*;
+use tracing::{debug, info, error, warn};
+use walkdir::WalkDir;
+use std::path::{Component, Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use hot_reload_common::{ScanReport, ScanDiagnostic};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub const VALID_EXTENSIONS: [&str; 3] = ["lua", "js", "dll"];
+
+// TODO : Manage ignored folders & files from UI and sync it with all clients
+pub const IGNORED_FOLDERS: [&str; 13] = [
+    "node_modules", ".git", "target",
+    ".idea", ".vscode", "vendor", "tmp", "temp",
+    "logs", "coverage", ".next", ".nuxt", ".cache"
+];
+pub const IGNORED_FILES: [&str; 10] = [
+    "package-lock.json", "yarn.lock", "pnpm-lock.yaml",
+    "README.md", "LICENSE", ".gitignore", ".env",
+    "tsconfig.json", "package.json", "webpack.config.js"
+];
+
+pub struct ScanResult {
+    pub index: ResourceIndex,
+    pub graph: DependencyGraph,
+    pub report: ScanReport,
+}
+
+/// Diagnostics for the dependency cycles of `graph`.
+pub fn cycle_diagnostics(graph: &DependencyGraph) -> Vec<ScanDiagnostic> {
+    graph
+        .cycles()
+        .into_iter()
+        .map(|resources| {
+            warn!("⚠️ Dependency cycle between {}", resources.join(", "));
+            ScanDiagnostic::DependencyCycle { resources }
+        })
+        .collect()
+}
+
+fn is_ignored_name(name: &str) -> bool {
+    IGNORED_FOLDERS.contains(&name) || name.starts_with('.')
+}
+
+/// Whether `path` goes through a folder the watcher never looks into (`node_modules`, `.git`, ...).
+pub fn in_ignored_folder(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_FOLDERS.contains(&name.to_str().unwrap_or_default()),
+        _ => false,
+    })
+}
+
+fn is_ignored_entry(entry: &walkdir::DirEntry) -> bool {
+    is_ignored_name(entry.file_name().to_str().unwrap_or_default())
+}
+
+/// Whether a file, given relative to its resource root, is one the watcher reloads on.
+pub fn is_watched(relative_path: &Path) -> bool {
+    let mut components = relative_path.components().peekable();
+    while let Some(component) = components.next() {
+        let Component::Normal(name) = component else {
+            continue;
+        };
+        let name = name.to_str().unwrap_or_default();
+        if components.peek().is_some() {
+            if is_ignored_name(name) {
+                return false;
+            }
+        } else if IGNORED_FILES.contains(&name) {
+            return false;
+        }
+    }
+
+    relative_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| VALID_EXTENSIONS.contains(&ext))
+        .unwrap_or(false)
+}
+
+fn full_path(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// A folder below a resource root holding its own manifest, a separate resource.
+fn is_nested_resource(entry: &walkdir::DirEntry) -> bool {
+    entry.depth() > 0 && entry.file_type().is_dir() && Manifest::find(entry.path()).is_some()
+}
+
+/// Watched files of the resource at `root`, as (relative path, full path).
+/// Nested resources are left out, they are indexed on their own.
+pub fn list_resource_files(root: &Path) -> Vec<(String, PathBuf)> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !is_ignored_entry(e) && !is_nested_resource(e))
+        .filter_map(|e| e.ok())
+        .filter(|file| file.file_type().is_file())
+        .filter_map(|file| {
+            let relative_path = file.path().strip_prefix(root).ok()?;
+            is_watched(relative_path)
+                .then(|| (relative_path.to_string_lossy().to_string(), file.path().to_path_buf()))
+        })
+        .collect()
+}
+
+/// Walks `dir` looking for folders holding a manifest.
+pub fn walk_for_resources(dir: &Path) -> (Vec<(String, PathBuf)>, Vec<ScanDiagnostic>) {
+    let mut diagnostics = Vec::new();
+    let mut resource_paths: Vec<(String, PathBuf)> = Vec::new();
+
+    for entry in WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !is_ignored_entry(e)) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let dir = e.path().map(full_path).unwrap_or_default();
+                warn!("⚠️ Unreadable directory {}: {}", dir, e);
+                diagnostics.push(ScanDiagnostic::UnreadableDirectory {
+                    path: dir,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        let resource_path = entry.path();
+        if Manifest::find(resource_path).is_none() {
+            continue;
+        }
+
+        let resource_name = resource_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        if let Some((parent, _)) = resource_paths
+            .iter()
+            .find(|(_, parent_path)| resource_path.starts_with(parent_path))
+        {
+            warn!("⚠️ Resource {} is nested inside {}", resource_name, parent);
+            diagnostics.push(ScanDiagnostic::NestedResource {
+                name: resource_name.clone(),
+                path: full_path(resource_path),
+                parent: parent.clone(),
+            });
+        }
+
+        match Manifest::load(resource_path) {
+            Ok(manifest) if !manifest.has_scripts() => {
+                warn!("⚠️ Manifest of {} declares no scripts", resource_name);
+                diagnostics.push(ScanDiagnostic::NoScripts {
+                    name: resource_name.clone(),
+                    path: full_path(resource_path),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!("⚠️ Unreadable manifest in {}: {}", resource_name, e);
+                diagnostics.push(ScanDiagnostic::UnreadableDirectory {
+                    path: full_path(resource_path),
+                    error: e.to_string(),
+                });
+            }
+        }
+
+        //info!("🔍 Ressource trouvée: {}", resource_name);
+        resource_paths.push((resource_name, resource_path.to_path_buf()));
+    }
+
+    (resource_paths, diagnostics)
+}
+
+/// Finds every resource below `path`. Top-level folders (`[core]`, `[jobs]`, ...)
+/// are walked in parallel.
+pub fn discover_resources(path: &Path) -> (Vec<(String, PathBuf)>, Vec<ScanDiagnostic>) {
+    let mut top_level: Vec<PathBuf> = match std::fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .filter(|e| !is_ignored_name(e.file_name().to_str().unwrap_or_default()))
+            .map(|e| e.path())
+            .collect(),
+        Err(e) => {
+            return (Vec::new(), vec![ScanDiagnostic::UnreadableDirectory {
+                path: full_path(path),
+                error: e.to_string(),
+            }]);
+        }
+    };
+    top_level.sort();
+
+    let walked: Vec<_> = top_level.par_iter().map(|dir| walk_for_resources(dir)).collect();
+    let mut resource_paths = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (found, issues) in walked {
+        resource_paths.extend(found);
+        diagnostics.extend(issues);
+    }
+
+    let mut by_name: HashMap<&str, Vec<String>> = HashMap::new();
+    for (name, resource_path) in &resource_paths {
+        by_name.entry(name.as_str()).or_default().push(full_path(resource_path));
+    }
+    let mut duplicates: Vec<_> = by_name.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    duplicates.sort_by_cached_key(|(name, _)| name.to_lowercase());
+    for (name, paths) in duplicates {
+        warn!("⚠️ Duplicate resource name {}: {}", name, paths.join(", "));
+        diagnostics.push(ScanDiagnostic::DuplicateResource {
+            name: name.to_string(),
+            paths,
+        });
+    }
+
+    // First folder found wins, the others are reported as duplicates
+    let mut seen = HashSet::new();
+    resource_paths.retain(|(name, _)| seen.insert(name.clone()));
+
+    (resource_paths, diagnostics)
+}
+
+/// Builds the resource index of the folder at `path`, listing and hashing
+/// every resource in parallel.
+pub fn build_index(path: &Path) -> Result<ScanResult, BoxError> {
+    info!("📂 Start scanning resources");
+    if !path.exists() {
+        error!("❌ Folder {} doesn't exist in current directory", path.display());
+        error!("❌ Ensure you run the executable from the root of the FiveM server");
+        return Err("Folder resources doesn't exist".into());
+    }
+
+    info!("📂 Scan folder: {}", path.display());
+    let started = Instant::now();
+    let (resource_paths, mut diagnostics) = discover_resources(path);
+
+    let hashed: Vec<_> = resource_paths
+        .par_iter()
+        .map(|(resource_name, resource_path)| {
+            let files: Vec<_> = list_resource_files(resource_path)
+                .into_iter()
+                .filter_map(|(file_path, full_path)| {
+                    debug!("📄 Fichier trouvé dans {}: {}", resource_name, file_path);
+                    read_entry(&full_path).map(|entry| (file_path, entry))
+                })
+                .collect();
+            (resource_name, resource_path, files)
+        })
+        .collect();
+
+    let mut index = ResourceIndex::default();
+    for (resource_name, resource_path, files) in hashed {
+        index.insert_resource(resource_name.clone(), resource_path);
+        for (file_path, entry) in files {
+            index.insert_file(resource_name, file_path, entry);
+        }
+    }
+
+    let graph = DependencyGraph::build(&index);
+    diagnostics.extend(cycle_diagnostics(&graph));
+
+    let resource_count = index.tree().len();
+    info!(
+        "🏁 Scan finished in {} ms, {} resources found, {} files hashed",
+        started.elapsed().as_millis(),
+        resource_count,
+        index.file_count()
+    );
+    if resource_count == 0 {
+        info!("⚠️ No resource with fxmanifest.lua found");
+    }
+    if !diagnostics.is_empty() {
+        warn!("⚠️ Scan reported {} issue(s)", diagnostics.len());
+    }
+
+    Ok(ScanResult {
+        index,
+        graph,
+        report: ScanReport { diagnostics },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_resources_are_not_listed_with_their_parent() {
+        let root = std::env::temp_dir().join(format!("hot-reload-scanner-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("client")).unwrap();
+        std::fs::create_dir_all(root.join("addons/child")).unwrap();
+        std::fs::write(root.join("fxmanifest.lua"), "client_script 'client/main.lua'").unwrap();
+        std::fs::write(root.join("client/main.lua"), "print(1)").unwrap();
+        std::fs::write(root.join("addons/child/fxmanifest.lua"), "server_script 'main.lua'").unwrap();
+        std::fs::write(root.join("addons/child/main.lua"), "print(2)").unwrap();
+
+        let mut files: Vec<String> = list_resource_files(&root).into_iter().map(|(relative, _)| relative.replace('\\', "/")).collect();
+        files.sort();
+        assert_eq!(files, ["client/main.lua", "fxmanifest.lua"]);
+        assert_eq!(list_resource_files(&root.join("addons/child")).len(), 2);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
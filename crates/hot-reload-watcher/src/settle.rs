@@ -0,0 +1,115 @@
+use crate::config::WatcherConfig;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use hot_reload_common::ChangeType;
+use tracing::info;
+
+type Observation = Option<(u64, Option<SystemTime>)>;
+
+#[derive(Debug)]
+struct PendingFile {
+    existed_before: bool,
+    saw_missing: bool,
+    observed: Observation,
+    stable_since: Instant,
+}
+
+/// Holds file events back until the file stopped moving.
+///
+/// Editors that save atomically (vim, JetBrains) remove or rename the target
+/// before writing it again, and `dotnet build` writes `.dll` files in several
+/// chunks. Instead of trusting the raw event kinds, every touched path waits
+/// until its size and mtime stayed the same for the configured interval, and
+/// the change type is decided from whether the file existed before and after.
+#[derive(Debug)]
+pub struct Settler {
+    pending: HashMap<PathBuf, PendingFile>,
+    interval: Duration,
+    dll_interval: Duration,
+}
+
+fn observe(path: &Path) -> Observation {
+    std::fs::metadata(path)
+        .ok()
+        .map(|meta| (meta.len(), meta.modified().ok()))
+}
+
+impl Settler {
+    pub fn new(config: &WatcherConfig) -> Self {
+        Self {
+            pending: HashMap::new(),
+            interval: Duration::from_millis(config.stability_interval_ms),
+            dll_interval: Duration::from_millis(config.dll_stability_interval_ms),
+        }
+    }
+
+    /// Records an event on `path`. `known` tells whether the file was indexed
+    /// before this burst of events started.
+    pub fn touch(&mut self, path: PathBuf, known: bool) {
+        let observed = observe(&path);
+        let now = Instant::now();
+        let pending = self.pending.entry(path).or_insert(PendingFile {
+            existed_before: known,
+            saw_missing: false,
+            observed,
+            stable_since: now,
+        });
+        pending.saw_missing |= observed.is_none();
+        pending.observed = observed;
+        pending.stable_since = now;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the files that settled since the last call, with the change
+    /// they amount to.
+    pub fn poll(&mut self) -> Vec<(PathBuf, ChangeType)> {
+        let now = Instant::now();
+        let (interval, dll_interval) = (self.interval, self.dll_interval);
+        let mut settled = Vec::new();
+
+        for (path, pending) in self.pending.iter_mut() {
+            let observed = observe(path);
+            if observed != pending.observed {
+                pending.saw_missing |= observed.is_none();
+                pending.observed = observed;
+                pending.stable_since = now;
+                continue;
+            }
+
+            let interval = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("dll") => dll_interval,
+                _ => interval,
+            };
+            if now.duration_since(pending.stable_since) >= interval {
+                settled.push(path.clone());
+            }
+        }
+
+        let mut changes = Vec::with_capacity(settled.len());
+        for path in settled {
+            let Some(pending) = self.pending.remove(&path) else {
+                continue;
+            };
+            let exists = pending.observed.is_some();
+            let change_type = match (pending.existed_before, exists) {
+                (true, true) => {
+                    if pending.saw_missing {
+                        info!("🔁 Atomic save detected: {}", path.display());
+                    }
+                    ChangeType::FileModified
+                }
+                (false, true) => ChangeType::FileAdded,
+                (true, false) => ChangeType::FileRemoved,
+                // Temporary file that came and went before settling
+                (false, false) => continue,
+            };
+            changes.push((path, change_type));
+        }
+
+        changes
+    }
+}
@@ -0,0 +1,254 @@
+use crate::config::Snapshots;
+use crate::eventlog::now_ms;
+use hot_reload_common::{SnapshotInfo, SnapshotList};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::xxh3_128;
+
+/// One saved version of the watched files of a resource.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    id: u64,
+    created_ms: u64,
+    /// Path relative to the resource root -> blob holding its content
+    files: BTreeMap<String, String>,
+    bytes: u64,
+    #[serde(default)]
+    before_rollback: bool,
+}
+
+impl Manifest {
+    fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id,
+            created_ms: self.created_ms,
+            files: self.files.len(),
+            bytes: self.bytes,
+            before_rollback: self.before_rollback,
+        }
+    }
+}
+
+/// What a rollback changed on disk.
+pub struct Restored {
+    pub id: u64,
+    /// Snapshot of the files as they were before, `None` when they matched the latest one
+    pub saved: Option<u64>,
+    pub written: usize,
+    pub removed: usize,
+    /// Files that could not be written or removed, the others were rolled back anyway
+    pub failed: Vec<String>,
+}
+
+/// Content-addressed copies of resources: `objects/` holds every file content
+/// once, named after its hash, and `resources/<name>/<id>.json` lists what a
+/// snapshot is made of. Only the last `keep` snapshots of a resource are kept.
+#[derive(Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl SnapshotStore {
+    pub fn new(config: &Snapshots) -> Self {
+        Self {
+            dir: PathBuf::from(&config.dir),
+            keep: config.keep.max(1),
+        }
+    }
+
+    fn objects(&self) -> PathBuf {
+        self.dir.join("objects")
+    }
+
+    fn resource_dir(&self, resource: &str) -> PathBuf {
+        self.dir.join("resources").join(resource)
+    }
+
+    /// Snapshots of the resource, newest first.
+    fn manifests(&self, resource: &str) -> Vec<Manifest> {
+        let Ok(entries) = std::fs::read_dir(self.resource_dir(resource)) else {
+            return Vec::new();
+        };
+        let mut manifests: Vec<Manifest> = entries
+            .flatten()
+            .filter_map(|entry| serde_json::from_slice(&std::fs::read(entry.path()).ok()?).ok())
+            .collect();
+        manifests.sort_by_key(|manifest| std::cmp::Reverse(manifest.id));
+        manifests
+    }
+
+    pub fn list(&self, resource: &str) -> SnapshotList {
+        SnapshotList {
+            resource: resource.to_string(),
+            snapshots: self.manifests(resource).iter().map(Manifest::info).collect(),
+        }
+    }
+
+    /// Snapshot lists of every resource that has at least one.
+    pub fn all(&self) -> Vec<SnapshotList> {
+        let Ok(entries) = std::fs::read_dir(self.dir.join("resources")) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| Some(self.list(entry.file_name().to_str()?)))
+            .filter(|list| !list.snapshots.is_empty())
+            .collect()
+    }
+
+    /// Saves the files of the resource, `None` when they match its latest snapshot.
+    pub fn take(&self, resource: &str, root: &Path, files: &[String]) -> io::Result<Option<SnapshotInfo>> {
+        self.save(resource, root, files, false)
+    }
+
+    fn save(&self, resource: &str, root: &Path, files: &[String], before_rollback: bool) -> io::Result<Option<SnapshotInfo>> {
+        std::fs::create_dir_all(self.objects())?;
+        let previous = self.manifests(resource);
+        let created_ms = now_ms();
+        let mut manifest = Manifest {
+            // Two snapshots can be taken within a millisecond, around a rollback
+            id: previous.first().map_or(created_ms, |latest| created_ms.max(latest.id + 1)),
+            created_ms,
+            files: BTreeMap::new(),
+            bytes: 0,
+            before_rollback,
+        };
+        for relative in files {
+            let Ok(content) = std::fs::read(root.join(relative)) else {
+                continue;
+            };
+            let blob = format!("{:032x}", xxh3_128(&content));
+            let object = self.objects().join(&blob);
+            if !object.exists() {
+                std::fs::write(&object, &content)?;
+            }
+            manifest.bytes += content.len() as u64;
+            manifest.files.insert(relative.clone(), blob);
+        }
+
+        if previous.first().is_some_and(|latest| latest.files == manifest.files) {
+            return Ok(None);
+        }
+        let resource_dir = self.resource_dir(resource);
+        std::fs::create_dir_all(&resource_dir)?;
+        std::fs::write(resource_dir.join(format!("{}.json", manifest.id)), serde_json::to_vec(&manifest)?)?;
+
+        if previous.len() >= self.keep {
+            for old in &previous[self.keep - 1..] {
+                let _ = std::fs::remove_file(resource_dir.join(format!("{}.json", old.id)));
+            }
+            self.collect_garbage();
+        }
+        Ok(Some(manifest.info()))
+    }
+
+    /// Writes back the files of a snapshot, the latest taken after a reload when
+    /// `id` is `None`, and removes the watched files it does not have. `current`
+    /// lists them, they are snapshotted first so the rollback can be undone.
+    /// A file that cannot be removed or written does not stop the others, it
+    /// is listed in `failed`.
+    pub fn restore(&self, resource: &str, id: Option<u64>, root: &Path, current: &[String]) -> io::Result<Restored> {
+        let manifest = self
+            .manifests(resource)
+            .into_iter()
+            .find(|manifest| id.map_or(!manifest.before_rollback, |id| manifest.id == id))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no snapshot of {}", resource)))?;
+        // Read before saving the current files, pruning may drop the target and its blobs
+        let contents = manifest
+            .files
+            .iter()
+            .map(|(relative, blob)| Ok((relative, std::fs::read(self.objects().join(blob))?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let saved = self.save(resource, root, current, true)?;
+
+        let mut restored = Restored {
+            id: manifest.id,
+            saved: saved.map(|info| info.id),
+            written: 0,
+            removed: 0,
+            failed: Vec::new(),
+        };
+        // Removals first, a file can be in the way of a folder the snapshot has
+        for relative in current.iter().filter(|relative| !manifest.files.contains_key(*relative)) {
+            match std::fs::remove_file(root.join(relative)) {
+                Ok(()) => restored.removed += 1,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => restored.failed.push(format!("{}: {}", relative, e)),
+            }
+        }
+        for (relative, content) in contents {
+            let path = root.join(relative);
+            if std::fs::read(&path).is_ok_and(|existing| existing == content) {
+                continue;
+            }
+            let written = match path.parent() {
+                Some(parent) => std::fs::create_dir_all(parent).and_then(|_| std::fs::write(&path, content)),
+                None => std::fs::write(&path, content),
+            };
+            match written {
+                Ok(()) => restored.written += 1,
+                Err(e) => restored.failed.push(format!("{}: {}", relative, e)),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Removes the blobs no snapshot refers to anymore.
+    fn collect_garbage(&self) {
+        let Ok(resources) = std::fs::read_dir(self.dir.join("resources")) else {
+            return;
+        };
+        let used: HashSet<String> = resources
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().map(|name| self.manifests(name)))
+            .flatten()
+            .flat_map(|manifest| manifest.files.into_values())
+            .collect();
+        let Ok(objects) = std::fs::read_dir(self.objects()) else {
+            return;
+        };
+        for object in objects.flatten() {
+            if object.file_name().to_str().is_some_and(|name| !used.contains(name)) {
+                let _ = std::fs::remove_file(object.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_saves_the_current_files_and_latest_skips_them() {
+        let base = std::env::temp_dir().join(format!("hot-reload-snapshots-{}", std::process::id()));
+        let root = base.join("demo");
+        std::fs::create_dir_all(&root).unwrap();
+        let store = SnapshotStore::new(&Snapshots {
+            dir: base.join("store").to_string_lossy().into_owned(),
+            ..Snapshots::default()
+        });
+        let files = vec!["client.lua".to_string()];
+
+        std::fs::write(root.join("client.lua"), "print('good')").unwrap();
+        let good = store.take("demo", &root, &files).unwrap().unwrap();
+        std::fs::write(root.join("client.lua"), "print('broken'").unwrap();
+
+        let restored = store.restore("demo", None, &root, &files).unwrap();
+        assert_eq!((restored.id, restored.written), (good.id, 1));
+        assert_eq!(std::fs::read_to_string(root.join("client.lua")).unwrap(), "print('good')");
+
+        let list = store.list("demo").snapshots;
+        assert_eq!(list.len(), 2);
+        assert!(list[0].before_rollback && Some(list[0].id) == restored.saved);
+        std::fs::write(root.join("client.lua"), "print('broken again'").unwrap();
+        assert_eq!(store.restore("demo", None, &root, &files).unwrap().id, good.id);
+
+        store.restore("demo", restored.saved, &root, &files).unwrap();
+        assert_eq!(std::fs::read_to_string(root.join("client.lua")).unwrap(), "print('broken'");
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
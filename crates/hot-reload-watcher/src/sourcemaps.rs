@@ -0,0 +1,194 @@
+use hot_reload_common::OriginalLocation;
+use sourcemap::{decode_data_url, locate_sourcemap_reference, DecodedMap, SourceMap};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// A parsed map and where it was found, to notice when either file changes.
+struct Loaded {
+    generated_at: SystemTime,
+    /// The `.map` file and its date, `None` for a map inlined in the script
+    file: Option<(PathBuf, SystemTime)>,
+    /// Directory of the map relative to the resource root, its sources are relative to it
+    dir: PathBuf,
+    map: Arc<SourceMap>,
+}
+
+/// Parsed source maps of generated scripts, read again once they change on disk.
+#[derive(Default)]
+pub struct SourceMaps {
+    cache: StdMutex<HashMap<PathBuf, Loaded>>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl SourceMaps {
+    /// Original position of `line`:`column` (1-based) in `file`, relative to the
+    /// resource `root`. The map is the one named by the `//# sourceMappingURL=`
+    /// comment of the file, a path or an inline data URL, or else `file.map`.
+    pub fn resolve(&self, root: &Path, file: &str, line: u32, column: Option<u32>) -> Option<OriginalLocation> {
+        let (map, map_dir) = self.load(root, file)?;
+
+        let line = line.checked_sub(1)?;
+        let token = map.lookup_token(line, column.unwrap_or(1).saturating_sub(1))?;
+        // The closest token may belong to an earlier line without mappings on this one
+        if token.get_dst_line() != line {
+            return None;
+        }
+
+        // The crate already prefixes relative sources with the `sourceRoot` of the map
+        Some(OriginalLocation {
+            file: source_path(&map_dir, token.get_source()?),
+            line: token.get_src_line() + 1,
+            column: token.get_src_col() + 1,
+        })
+    }
+
+    fn load(&self, root: &Path, file: &str) -> Option<(Arc<SourceMap>, PathBuf)> {
+        let generated = root.join(file);
+        let generated_at = modified(&generated)?;
+        let mut cache = self.cache.lock().ok()?;
+        if let Some(loaded) = cache.get(&generated) {
+            let fresh = loaded.generated_at == generated_at
+                && loaded.file.as_ref().is_none_or(|(path, at)| modified(path) == Some(*at));
+            if fresh {
+                return Some((loaded.map.clone(), loaded.dir.clone()));
+            }
+        }
+
+        let file_dir = Path::new(file).parent().unwrap_or(Path::new(""));
+        let reference = File::open(&generated).ok().and_then(|script| locate_sourcemap_reference(script).ok().flatten());
+        let url = reference.as_ref().map(|reference| reference.get_url());
+        let loaded = match url {
+            Some(url) if url.starts_with("data:") => {
+                // Bundlers add a `;charset=utf-8` the crate does not expect
+                let (_, data) = url.split_once(";base64,")?;
+                let map = match decode_data_url(&format!("data:application/json;base64,{}", data)) {
+                    Ok(DecodedMap::Regular(map)) => map,
+                    Ok(_) => return None,
+                    Err(e) => {
+                        warn!("⚠️ Unreadable inline source map in {}: {}", generated.display(), e);
+                        return None;
+                    }
+                };
+                Loaded {
+                    generated_at,
+                    file: None,
+                    dir: file_dir.to_path_buf(),
+                    map: Arc::new(map),
+                }
+            }
+            _ => {
+                // Only local paths are followed, `//# sourceMappingURL=https://...` is ignored
+                let relative = url
+                    .filter(|url| !url.contains("://"))
+                    .map(|url| file_dir.join(url.split(['?', '#']).next().unwrap_or(url)))
+                    .filter(|relative| root.join(relative).is_file())
+                    .unwrap_or_else(|| PathBuf::from(format!("{}.map", file)));
+                let path = root.join(&relative);
+                let map_at = modified(&path)?;
+                let map = File::open(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|map| SourceMap::from_reader(map).map_err(|e| e.to_string()));
+                let map = match map {
+                    Ok(map) => map,
+                    Err(e) => {
+                        warn!("⚠️ Unreadable source map {}: {}", path.display(), e);
+                        return None;
+                    }
+                };
+                Loaded {
+                    generated_at,
+                    file: Some((path, map_at)),
+                    dir: relative.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    map: Arc::new(map),
+                }
+            }
+        };
+
+        let result = (loaded.map.clone(), loaded.dir.clone());
+        cache.insert(generated, loaded);
+        Some(result)
+    }
+}
+
+/// `../src/main.ts` is relative to the map, `webpack://myres/./src/main.ts`
+/// to the project root, which is the resource root for our resources.
+fn source_path(map_dir: &Path, source: &str) -> String {
+    let path = match source.split_once("://") {
+        Some((_, rest)) => PathBuf::from(rest.split_once('/').map(|(_, path)| path).unwrap_or(rest)),
+        None => map_dir.join(source),
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generated line 2 comes from line 3 of the only source.
+    fn map_json(source: &str, source_root: &str) -> String {
+        format!(r#"{{"version":3,"file":"server.js","sourceRoot":"{}","sources":["{}"],"names":[],"mappings":";AAEA"}}"#, source_root, source)
+    }
+
+    fn resource(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("hot-reload-maps-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(root.join("dist/maps")).unwrap();
+        root
+    }
+
+    #[test]
+    fn source_paths() {
+        assert_eq!(source_path(Path::new("dist"), "webpack://myres/./src/server/main.ts"), "src/server/main.ts");
+        assert_eq!(source_path(Path::new("dist"), "webpack:///src/main.ts"), "src/main.ts");
+        assert_eq!(source_path(Path::new("dist/server"), "../../src/main.ts"), "src/main.ts");
+        assert_eq!(source_path(Path::new(""), "main.ts"), "main.ts");
+    }
+
+    #[test]
+    fn map_next_to_the_script() {
+        let root = resource("next");
+        std::fs::write(root.join("dist/server.js"), "a();\nb();\n").unwrap();
+        std::fs::write(root.join("dist/server.js.map"), map_json("../src/main.ts", "")).unwrap();
+        let original = SourceMaps::default().resolve(&root, "dist/server.js", 2, Some(1)).unwrap();
+        assert_eq!((original.file.as_str(), original.line), ("src/main.ts", 3));
+        assert!(SourceMaps::default().resolve(&root, "dist/server.js", 1, Some(1)).is_none());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn map_named_by_the_script_with_a_source_root() {
+        let root = resource("url");
+        std::fs::write(root.join("dist/server.js"), "a();\nb();\n//# sourceMappingURL=maps/server.js.map\n").unwrap();
+        std::fs::write(root.join("dist/maps/server.js.map"), map_json("main.ts", "../../src")).unwrap();
+        let original = SourceMaps::default().resolve(&root, "dist/server.js", 2, None).unwrap();
+        assert_eq!((original.file.as_str(), original.line), ("src/main.ts", 3));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn inline_map() {
+        let root = resource("inline");
+        let url = SourceMap::from_slice(map_json("../src/main.ts", "").as_bytes()).unwrap().to_data_url().unwrap();
+        std::fs::write(root.join("dist/server.js"), format!("a();\nb();\n//# sourceMappingURL={}\n", url)).unwrap();
+        let original = SourceMaps::default().resolve(&root, "dist/server.js", 2, Some(1)).unwrap();
+        assert_eq!((original.file.as_str(), original.line), ("src/main.ts", 3));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
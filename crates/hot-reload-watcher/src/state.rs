@@ -0,0 +1,572 @@
+use crate::breaker::Breakers;
+use crate::config::WatcherConfig;
+use crate::eventlog::{now_ms, EventLog};
+use crate::fx::FxRequest;
+use crate::pipeline::merge_change;
+use crate::graph::DependencyGraph;
+use crate::history::HistoryLog;
+use crate::index::ResourceIndex;
+use crate::scanner::{cycle_diagnostics, ScanResult};
+use crate::snapshots::SnapshotStore;
+use crate::sourcemaps::SourceMaps;
+use crate::stats::Stats;
+use hot_reload_common::{
+    parse_script_errors, BreakerState, HistoryEntry, HistoryEvent, Severity, ConsoleLine, LintReport, PauseState, PendingChange, PendingChanges, ApplyMode, ResourceChange, ReloadStrategies, ReloadStrategy, ResourceState, ResourceStateChange, ResourceStates, ResourceGraph, ScanDiagnostic, ScanReport, ScriptError,
+    ScriptErrorParser, WatcherMessage,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+
+const PIPELINE_LOG_CAPACITY: usize = 500;
+const SCRIPT_ERRORS_CAPACITY: usize = 100;
+const CONSOLE_HISTORY_CAPACITY: usize = 500;
+/// A stack trace is over once its channel stays quiet this long
+const STACK_TRACE_IDLE: Duration = Duration::from_millis(500);
+
+/// State shared by the file watcher, the reload pipeline and every client connection.
+pub struct WatcherState {
+    pub config: WatcherConfig,
+    pub index: StdMutex<ResourceIndex>,
+    pub graph: StdMutex<DependencyGraph>,
+    pub scan_report: StdMutex<ScanReport>,
+    /// Editable from the UI, `config.reload_strategies` is only the startup value
+    pub strategies: StdMutex<HashMap<String, ReloadStrategy>>,
+    pub stats: Stats,
+    pub pipeline_log: StdMutex<EventLog>,
+    /// Latest non-empty report per resource, file and linter, sent to new clients
+    pub lint_reports: StdMutex<BTreeMap<(String, String, String), LintReport>>,
+    /// Latest script errors printed by FXServer, sent to new clients
+    pub script_errors: StdMutex<VecDeque<ScriptError>>,
+    pub source_maps: SourceMaps,
+    /// Global switch holding automatic reloads back, `pending` is filled from `held`
+    pause: StdMutex<PauseState>,
+    held: StdMutex<BTreeMap<String, ResourceChange>>,
+    breakers: StdMutex<Breakers>,
+    pub snapshots: SnapshotStore,
+    history: StdMutex<HistoryLog>,
+    /// Mode and preview of the changes collected in manual mode, kept by the pipeline
+    pending: StdMutex<PendingChanges>,
+    /// Last known state of every resource on the server, sent to new clients
+    pub resource_states: StdMutex<HashMap<String, ResourceState>>,
+    /// Latest console lines, sent to new clients
+    pub console_history: StdMutex<VecDeque<ConsoleLine>>,
+    /// Script errors being read from the console, per channel, with their last line
+    console_errors: StdMutex<HashMap<String, (ScriptErrorParser, Instant)>>,
+    /// Typed messages for every connected client
+    pub events: broadcast::Sender<WatcherMessage>,
+    /// Raw FXServer answers, batched by each connection
+    pub fivem: broadcast::Sender<String>,
+    /// FXServer console lines, kept apart so a burst of output can't push out other events
+    pub console: broadcast::Sender<ConsoleLine>,
+    /// Console commands and resource actions of the clients, run by the pipeline
+    pub requests: mpsc::UnboundedSender<FxRequest>,
+}
+
+impl WatcherState {
+    pub fn new(config: WatcherConfig, scan: ScanResult, requests: mpsc::UnboundedSender<FxRequest>) -> Self {
+        let (events, _) = broadcast::channel(256);
+        let (fivem, _) = broadcast::channel(256);
+        let (console, _) = broadcast::channel(2048);
+        let stats = Stats::default();
+        stats.set_indexed_files(scan.index.file_count());
+
+        Self {
+            strategies: StdMutex::new(config.reload_strategies.clone()),
+            breakers: StdMutex::new(Breakers::new(config.circuit_breaker.clone())),
+            snapshots: SnapshotStore::new(&config.snapshots),
+            history: StdMutex::new(HistoryLog::new(&config.history)),
+            pending: StdMutex::new(PendingChanges {
+                mode: config.apply_mode,
+                changes: Vec::new(),
+            }),
+            config,
+            index: StdMutex::new(scan.index),
+            graph: StdMutex::new(scan.graph),
+            scan_report: StdMutex::new(scan.report),
+            stats,
+            pipeline_log: StdMutex::new(EventLog::new(PIPELINE_LOG_CAPACITY)),
+            lint_reports: StdMutex::new(BTreeMap::new()),
+            script_errors: StdMutex::new(VecDeque::new()),
+            source_maps: SourceMaps::default(),
+            pause: StdMutex::new(PauseState::default()),
+            held: StdMutex::new(BTreeMap::new()),
+            resource_states: StdMutex::new(HashMap::new()),
+            console_history: StdMutex::new(VecDeque::new()),
+            console_errors: StdMutex::new(HashMap::new()),
+            events,
+            fivem,
+            console,
+            requests,
+        }
+    }
+
+    /// Appends to the history file, `user` is the client behind it.
+    pub fn record(&self, user: Option<&str>, resources: Vec<String>, event: HistoryEvent) {
+        let entry = HistoryEntry {
+            timestamp_ms: now_ms(),
+            user: user.map(str::to_string),
+            resources,
+            event,
+        };
+        if let Ok(mut history) = self.history.lock() {
+            history.append(&entry);
+        }
+    }
+
+    pub fn log(&self, stage: &str, message: impl Into<String>) {
+        if let Ok(mut log) = self.pipeline_log.lock() {
+            log.push(stage, message);
+        }
+    }
+
+    pub fn strategy(&self, resource: &str) -> ReloadStrategy {
+        self.strategies
+            .lock()
+            .ok()
+            .and_then(|strategies| strategies.get(resource).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Changes the strategy of a resource and writes it to the config file.
+    /// Only `reload_strategies` is rewritten, a file that does not parse is left alone.
+    pub fn set_strategy(&self, resource: String, strategy: ReloadStrategy) -> Result<(), String> {
+        let Ok(mut strategies) = self.strategies.lock() else {
+            return Ok(());
+        };
+        let mut updated = strategies.clone();
+        if strategy == ReloadStrategy::Auto {
+            updated.remove(&resource);
+        } else {
+            updated.insert(resource, strategy);
+        }
+
+        // Read the file again, the running config holds the resolved watch mode
+        let value = serde_json::to_value(&updated).map_err(|e| e.to_string())?;
+        WatcherConfig::save_key("reload_strategies", value)?;
+        *strategies = updated.clone();
+        drop(strategies);
+        self.broadcast(WatcherMessage::ReloadStrategies(ReloadStrategies { strategies: updated, error: None }));
+        Ok(())
+    }
+
+    pub fn graph_snapshot(&self) -> Option<ResourceGraph> {
+        let graph = self.graph.lock().ok()?;
+        Some(graph.snapshot(&self.config.no_cascade))
+    }
+
+    /// Tells the clients about a new dependency graph and its cycles.
+    pub fn graph_changed(&self) {
+        self.refresh_cycles();
+        if let Some(graph) = self.graph_snapshot() {
+            self.broadcast(WatcherMessage::Graph(graph));
+        }
+    }
+
+    /// Replaces the reported dependency cycles after the graph changed and
+    /// tells the clients when they differ.
+    fn refresh_cycles(&self) {
+        let cycles = match self.graph.lock() {
+            Ok(graph) => cycle_diagnostics(&graph),
+            Err(_) => return,
+        };
+        let Ok(mut report) = self.scan_report.lock() else {
+            return;
+        };
+        let previous: Vec<_> = report
+            .diagnostics
+            .iter()
+            .filter(|d| matches!(d, ScanDiagnostic::DependencyCycle { .. }))
+            .cloned()
+            .collect();
+        if previous == cycles {
+            return;
+        }
+
+        report.diagnostics.retain(|d| !matches!(d, ScanDiagnostic::DependencyCycle { .. }));
+        report.diagnostics.extend(cycles);
+        self.broadcast(WatcherMessage::ScanReport(report.clone()));
+    }
+
+    pub fn lint_finished(&self, report: LintReport) {
+        if let Ok(mut reports) = self.lint_reports.lock() {
+            let key = (report.resource.clone(), report.file.clone(), report.source.clone());
+            if report.diagnostics.is_empty() {
+                reports.remove(&key);
+            } else {
+                reports.insert(key, report.clone());
+            }
+        }
+        let (resource, file) = (report.resource.clone(), report.file.clone());
+        self.broadcast(WatcherMessage::Lint(report));
+        let linted = self.breakers.lock().ok().and_then(|mut breakers| breakers.linted(&resource, &file));
+        if linted.is_some_and(|files| !self.has_lint_errors(&resource, &files)) {
+            info!("🩹 {} lints without errors again", resource);
+            self.reset_breaker(&resource, false);
+        }
+    }
+
+    /// Errors in these files only, older errors elsewhere in the resource did not trip it.
+    fn has_lint_errors(&self, resource: &str, files: &BTreeSet<String>) -> bool {
+        self.lint_reports.lock().is_ok_and(|reports| {
+            reports
+                .values()
+                .filter(|report| report.resource == resource && files.contains(&report.file))
+                .any(|report| report.diagnostics.iter().any(|d| d.severity == Severity::Error))
+        })
+    }
+
+    /// Drops the diagnostics of a removed file.
+    pub fn clear_lint(&self, resource: &str, file: &str) {
+        let cleared: Vec<LintReport> = match self.lint_reports.lock() {
+            Ok(mut reports) => {
+                let keys: Vec<_> = reports
+                    .keys()
+                    .filter(|(r, f, _)| r == resource && f == file)
+                    .cloned()
+                    .collect();
+                keys.into_iter().filter_map(|key| reports.remove(&key)).collect()
+            }
+            Err(_) => return,
+        };
+        for report in cleared {
+            self.broadcast(WatcherMessage::Lint(LintReport {
+                diagnostics: Vec::new(),
+                ..report
+            }));
+        }
+    }
+
+    /// Forwards FXServer output to the clients and reports the script errors found in it.
+    pub fn fx_output(&self, text: String) {
+        for error in parse_script_errors(&text) {
+            self.script_error(error);
+        }
+        let _ = self.fivem.send(text);
+    }
+
+    /// Relays console output of FXServer and reports the script errors printed in it.
+    pub fn console_output(&self, channel: &str, message: &str) {
+        let mut errors = Vec::new();
+        if let Ok(mut parsers) = self.console_errors.lock() {
+            let (parser, last_line) = parsers.entry(channel.to_string()).or_insert_with(|| (ScriptErrorParser::default(), Instant::now()));
+            *last_line = Instant::now();
+            for line in message.lines().filter(|line| !line.trim().is_empty()) {
+                errors.extend(parser.push(Some(channel), line));
+                let line = ConsoleLine {
+                    channel: channel.to_string(),
+                    message: line.to_string(),
+                    timestamp_ms: now_ms(),
+                };
+                if let Ok(mut history) = self.console_history.lock() {
+                    if history.len() >= CONSOLE_HISTORY_CAPACITY {
+                        history.pop_front();
+                    }
+                    history.push_back(line.clone());
+                }
+                let _ = self.console.send(line);
+            }
+        }
+        for error in errors {
+            self.script_error(error);
+        }
+    }
+
+    /// Reports the errors whose stack trace stopped coming in.
+    pub fn flush_console_errors(&self) {
+        let errors: Vec<ScriptError> = match self.console_errors.lock() {
+            Ok(mut parsers) => parsers
+                .values_mut()
+                .filter(|(_, last_line)| last_line.elapsed() >= STACK_TRACE_IDLE)
+                .filter_map(|(parser, _)| parser.finish())
+                .collect(),
+            Err(_) => return,
+        };
+        for error in errors {
+            self.script_error(error);
+        }
+    }
+
+    fn script_error(&self, mut error: ScriptError) {
+        self.map_to_sources(&mut error);
+        self.log("script_error", format!("{}: {}", error.resource.as_deref().unwrap_or("?"), error.message));
+        if let Ok(mut errors) = self.script_errors.lock() {
+            if errors.len() >= SCRIPT_ERRORS_CAPACITY {
+                errors.pop_front();
+            }
+            errors.push_back(error.clone());
+        }
+        let failed = match (&error.resource, self.breakers.lock()) {
+            (Some(resource), Ok(mut breakers)) => breakers.script_error(resource, &error.message, now_ms()),
+            _ => None,
+        };
+        self.broadcast(WatcherMessage::ScriptError(error));
+        if let Some((breaker, trips)) = failed {
+            self.breaker_changed(breaker, trips);
+        }
+    }
+
+    /// Fills the TypeScript positions of an error raised in compiled JavaScript.
+    fn map_to_sources(&self, error: &mut ScriptError) {
+        let roots: HashMap<String, PathBuf> = match self.index.lock() {
+            Ok(index) => error
+                .resource
+                .iter()
+                .chain(error.frames.iter().filter_map(|frame| frame.resource.as_ref()))
+                .filter_map(|name| Some((name.clone(), index.resource(name)?.root.clone())))
+                .collect(),
+            Err(_) => return,
+        };
+
+        if let (Some(root), Some(file), Some(line)) = (
+            error.resource.as_ref().and_then(|name| roots.get(name)),
+            &error.file,
+            error.line,
+        ) {
+            error.original = self.source_maps.resolve(root, file, line, error.column);
+        }
+        for frame in &mut error.frames {
+            if let (Some(root), Some(line)) = (frame.resource.as_ref().and_then(|name| roots.get(name)), frame.line) {
+                frame.original = self.source_maps.resolve(root, &frame.file, line, frame.column);
+            }
+        }
+    }
+
+    pub fn pause_state(&self) -> PauseState {
+        let mut pause = self.pause.lock().map(|pause| pause.clone()).unwrap_or_default();
+        if let Ok(held) = self.held.lock() {
+            pause.pending = held.values().cloned().collect();
+        }
+        pause
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.lock().map(|pause| pause.paused).unwrap_or(false)
+    }
+
+    /// Holds automatic reloads back until `resume`, or until `duration_ms` ran out.
+    /// Pausing again only updates the reason and the expiry.
+    pub fn pause(&self, reason: Option<String>, duration_ms: Option<u64>) {
+        if let Ok(mut pause) = self.pause.lock() {
+            let now = now_ms();
+            pause.since_ms = pause.since_ms.filter(|_| pause.paused).or(Some(now));
+            pause.paused = true;
+            pause.until_ms = duration_ms.map(|duration| now + duration);
+            pause.reason = reason;
+        }
+        let pause = self.pause_state();
+        info!("⏸️ Automatic reloads paused: {}", pause.reason.as_deref().unwrap_or("no reason given"));
+        self.log("pause", format!("paused: {}", pause.reason.as_deref().unwrap_or("-")));
+        self.broadcast(WatcherMessage::Pause(pause));
+    }
+
+    /// Lets reloads through again and hands back what changed meanwhile, nothing when discarded.
+    pub fn resume(&self, apply: bool) -> BTreeMap<String, ResourceChange> {
+        if let Ok(mut pause) = self.pause.lock() {
+            *pause = PauseState::default();
+        }
+        let held = self.held.lock().map(|mut held| std::mem::take(&mut *held)).unwrap_or_default();
+        let verb = if apply { "reloading" } else { "discarding" };
+        info!("▶️ Automatic reloads resumed, {} {} held change(s)", verb, held.len());
+        self.log("pause", format!("resumed, {} {} held change(s)", verb, held.len()));
+        self.broadcast(WatcherMessage::Pause(self.pause_state()));
+        if apply {
+            held
+        } else {
+            BTreeMap::new()
+        }
+    }
+
+    /// Whether a pause with a duration ran out.
+    pub fn pause_expired(&self) -> bool {
+        self.pause
+            .lock()
+            .map(|pause| pause.paused && pause.until_ms.is_some_and(|until| now_ms() >= until))
+            .unwrap_or(false)
+    }
+
+    /// Keeps changes for after the pause, `false` when reloads are not paused.
+    pub fn hold(&self, changes: &BTreeMap<String, ResourceChange>) -> bool {
+        if !self.is_paused() {
+            return false;
+        }
+        if let Ok(mut held) = self.held.lock() {
+            for change in changes.values() {
+                merge_change(&mut held, change.clone());
+            }
+        }
+        self.log("pause", format!("held: {}", changes.keys().cloned().collect::<Vec<_>>().join(", ")));
+        self.broadcast(WatcherMessage::Pause(self.pause_state()));
+        true
+    }
+
+    pub fn breaker_states(&self) -> Vec<BreakerState> {
+        self.breakers.lock().map(|breakers| breakers.states()).unwrap_or_default()
+    }
+
+    pub fn breaker_state(&self, resource: &str) -> Option<BreakerState> {
+        self.breakers.lock().ok().map(|breakers| breakers.state_of(resource))
+    }
+
+    pub fn is_tripped(&self, resource: &str) -> bool {
+        self.breakers.lock().is_ok_and(|breakers| breakers.is_tripped(resource))
+    }
+
+    /// An automatic reload of the resource was sent to FXServer.
+    pub fn reload_started(&self, resource: &str) {
+        if let Ok(mut breakers) = self.breakers.lock() {
+            breakers.reloaded(resource, now_ms());
+        }
+    }
+
+    pub fn reload_failed(&self, resource: &str, error: String) {
+        let failed = self.breakers.lock().ok().map(|mut breakers| breakers.failed(resource, error, now_ms()));
+        if let Some((breaker, trips)) = failed {
+            self.breaker_changed(breaker, trips);
+        }
+    }
+
+    pub fn failed_since(&self, resource: &str, at: u64) -> bool {
+        self.breakers.lock().is_ok_and(|breakers| breakers.failed_since(resource, at))
+    }
+
+    /// Keeps a change of a tripped resource until its breaker is reset, `false` when it is not tripped.
+    pub fn block(&self, change: &ResourceChange) -> bool {
+        let Ok(mut breakers) = self.breakers.lock() else {
+            return false;
+        };
+        if !breakers.is_tripped(&change.resource_name) {
+            return false;
+        }
+        info!("⛔ {} keeps failing, not reloading it", change.resource_name);
+        self.log("breaker", format!("{} blocked", change.resource_name));
+        breakers.block(change.clone());
+        true
+    }
+
+    /// Lets the resource reload again and reloads what was blocked meanwhile. A client
+    /// reset forgets the failures, a fix keeps them so one more failure trips it again.
+    pub fn reset_breaker(&self, resource: &str, forget_failures: bool) {
+        let reset = self.breakers.lock().ok().and_then(|mut breakers| breakers.reset(resource, forget_failures));
+        let Some((breaker, blocked)) = reset else {
+            return;
+        };
+        info!("🔌 Circuit breaker of {} reset", resource);
+        self.log("breaker", format!("{} reset", resource));
+        self.broadcast(WatcherMessage::Breaker(breaker));
+        if !blocked.is_empty() {
+            let _ = self.requests.send(FxRequest::Resume(blocked));
+        }
+    }
+
+    /// A manual reload FXServer accepted closes the breaker of a resource no linter covers.
+    /// The failures stay counted, the blocked changes are dropped as the reload loaded them.
+    pub fn manual_reload_succeeded(&self, resource: &str) {
+        let reset = self.breakers.lock().ok().and_then(|mut breakers| breakers.reset(resource, false));
+        let Some((breaker, _)) = reset else {
+            return;
+        };
+        info!("🩹 {} reloaded by hand, auto-reload resumes", resource);
+        self.log("breaker", format!("{} reset by a manual reload", resource));
+        self.broadcast(WatcherMessage::Breaker(breaker));
+    }
+
+    /// A rollback asked by a client starts over: failures are forgotten and the
+    /// changes blocked meanwhile are dropped, the snapshot replaced them on disk.
+    pub fn rolled_back(&self, resource: &str) {
+        let reset = self.breakers.lock().ok().and_then(|mut breakers| breakers.reset(resource, true));
+        if let Some((breaker, blocked)) = reset.filter(|(breaker, _)| breaker.last_error.is_some()) {
+            if !blocked.is_empty() {
+                self.log("breaker", format!("{} rolled back, blocked changes dropped", resource));
+            }
+            self.broadcast(WatcherMessage::Breaker(breaker));
+        }
+    }
+
+    /// Called for every failed reload.
+    fn breaker_changed(&self, breaker: BreakerState, trips: bool) {
+        if trips {
+            warn!("⛔ {} failed {} times, auto-reload stopped for it: {}", breaker.resource, breaker.failures, breaker.last_error.as_deref().unwrap_or("-"));
+        }
+        self.log("breaker", format!("{}: {} failure(s), tripped: {}", breaker.resource, breaker.failures, breaker.tripped));
+        if trips && self.config.snapshots.enabled && self.config.snapshots.auto_rollback {
+            let _ = self.requests.send(FxRequest::Rollback {
+                resource: breaker.resource.clone(),
+                snapshot: None,
+                user: None,
+            });
+        }
+        self.broadcast(WatcherMessage::Breaker(breaker));
+    }
+
+    pub fn pending_changes(&self) -> PendingChanges {
+        self.pending.lock().map(|pending| pending.clone()).unwrap_or_default()
+    }
+
+    pub fn apply_mode(&self) -> ApplyMode {
+        self.pending.lock().map(|pending| pending.mode).unwrap_or_default()
+    }
+
+    /// Switches the mode for every client, back in auto the collected changes are applied.
+    pub fn set_apply_mode(&self, mode: ApplyMode, user: &str) {
+        let Ok(mut pending) = self.pending.lock() else {
+            return;
+        };
+        pending.mode = mode;
+        let collected: Vec<String> = pending.changes.iter().map(|change| change.resource.clone()).collect();
+        drop(pending);
+        info!("🧺 Apply mode set to {:?}", mode);
+        self.log("apply", format!("mode: {:?}", mode));
+        if mode == ApplyMode::Auto && !collected.is_empty() {
+            let _ = self.requests.send(FxRequest::Apply {
+                resources: collected,
+                user: user.to_string(),
+            });
+        }
+        self.broadcast(WatcherMessage::PendingChanges(self.pending_changes()));
+    }
+
+    /// Replaces the preview of the collected changes and sends it to the clients.
+    pub fn set_pending(&self, changes: Vec<PendingChange>) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.changes = changes;
+        }
+        self.broadcast(WatcherMessage::PendingChanges(self.pending_changes()));
+    }
+
+    /// Replaces the resource states with a full table from FXServer, clients hear about it when it differs.
+    pub fn resource_states_received(&self, states: HashMap<String, ResourceState>) {
+        {
+            let Ok(mut current) = self.resource_states.lock() else {
+                return;
+            };
+            if *current == states {
+                return;
+            }
+            current.clone_from(&states);
+        }
+        self.broadcast(WatcherMessage::ResourceStates(ResourceStates { states }));
+    }
+
+    pub fn resource_state_changed(&self, resource: String, state: ResourceState) {
+        {
+            let Ok(mut current) = self.resource_states.lock() else {
+                return;
+            };
+            if current.get(&resource) == Some(&state) {
+                return;
+            }
+            current.insert(resource.clone(), state);
+        }
+        self.log("state", format!("{} is {:?}", resource, state));
+        self.broadcast(WatcherMessage::ResourceStateChange(ResourceStateChange { resource, state }));
+    }
+
+    /// Sends a message to every connected client, nobody listening is fine.
+    pub fn broadcast(&self, message: WatcherMessage) {
+        let _ = self.events.send(message);
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use hot_reload_common::WatcherStats;
+
+/// Counters shared between the notify callback and the connection loop.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub indexed_files: AtomicU64,
+    pub processed_events: AtomicU64,
+    pub suppressed_events: AtomicU64,
+    pub dropped_events: AtomicU64,
+}
+
+impl Stats {
+    pub fn set_indexed_files(&self, count: usize) {
+        self.indexed_files.store(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> WatcherStats {
+        WatcherStats {
+            indexed_files: self.indexed_files.load(Ordering::Relaxed),
+            processed_events: self.processed_events.load(Ordering::Relaxed),
+            suppressed_events: self.suppressed_events.load(Ordering::Relaxed),
+            dropped_events: self.dropped_events.load(Ordering::Relaxed),
+        }
+    }
+}
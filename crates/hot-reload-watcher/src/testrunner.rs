@@ -0,0 +1,372 @@
+use crate::config::{TestFormat, TestRunner};
+use crate::hooks::shell;
+use crate::state::WatcherState;
+use hot_reload_common::{TestCase, TestReport, TestStatus, WatcherMessage};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// `ok 3 - name # SKIP why` or `not ok 3 name`, `okay` is not a test line.
+fn tap_test_line(line: &str) -> Option<(bool, &str)> {
+    let (passed, rest) = match line.strip_prefix("not ok") {
+        Some(rest) => (false, rest),
+        None => (true, line.strip_prefix("ok")?),
+    };
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some((passed, rest))
+}
+
+/// Parses TAP output, YAML blocks and comments following a failure become its message.
+/// Subtests (`# Subtest: name` and indented lines) name the suite of the tests they hold,
+/// the line summing a subtest up is left out.
+pub fn parse_tap(output: &str) -> Vec<TestCase> {
+    let mut cases: Vec<TestCase> = Vec::new();
+    let mut details: Option<usize> = None;
+    // Open subtests: indent, name and whether tests were seen inside
+    let mut subtests: Vec<(usize, String, bool)> = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if let Some(name) = trimmed.strip_prefix("# Subtest:") {
+            subtests.push((indent, name.trim().to_string(), false));
+            details = None;
+            continue;
+        }
+        // `1..4` closes the test list it belongs to
+        if trimmed.split_once("..").is_some_and(|(first, _)| !first.is_empty() && first.chars().all(|c| c.is_ascii_digit())) {
+            details = None;
+            continue;
+        }
+        let Some((passed, rest)) = tap_test_line(trimmed) else {
+            // Diagnostics of the last failure, until the next test line
+            if let Some(case) = details.and_then(|i| cases.get_mut(i)) {
+                let detail = trimmed.trim_start_matches('#').trim();
+                if !detail.is_empty() && detail != "---" && detail != "..." {
+                    let message = case.message.get_or_insert_with(String::new);
+                    if !message.is_empty() {
+                        message.push('\n');
+                    }
+                    message.push_str(detail);
+                }
+            }
+            continue;
+        };
+
+        let (description, directive) = match rest.split_once(" # ") {
+            Some((description, directive)) => (description, Some(directive.trim())),
+            None => (rest, None),
+        };
+        let description = description
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start()
+            .trim_start_matches("- ")
+            .trim();
+
+        // Subtests deeper than this line are over
+        while subtests.last().is_some_and(|(at, _, _)| *at > indent) {
+            subtests.pop();
+        }
+        let closes = subtests.last().is_some_and(|(at, name, _)| *at == indent && name == description);
+        let summary = closes && subtests.pop().is_some_and(|(_, _, nested)| nested);
+        for subtest in subtests.iter_mut().filter(|(at, _, _)| *at < indent) {
+            subtest.2 = true;
+        }
+        if summary {
+            details = None;
+            continue;
+        }
+
+        let directive_kind = directive.map(|d| d.to_ascii_uppercase());
+        let status = match directive_kind.as_deref() {
+            Some(d) if d.starts_with("SKIP") => TestStatus::Skipped,
+            // A failing TODO test is expected to fail
+            Some(d) if d.starts_with("TODO") => TestStatus::Skipped,
+            _ if passed => TestStatus::Passed,
+            _ => TestStatus::Failed,
+        };
+
+        details = (status == TestStatus::Failed).then_some(cases.len());
+        cases.push(TestCase {
+            name: description.to_string(),
+            suite: subtests.iter().rev().find(|(at, _, _)| *at < indent).map(|(_, name, _)| name.clone()),
+            status,
+            message: directive.filter(|_| status == TestStatus::Skipped).map(String::from),
+        });
+    }
+    cases
+}
+
+/// Parses a JUnit XML report, as written by busted, vitest, jest or mocha.
+pub fn parse_junit(report: &str) -> Result<Vec<TestCase>, String> {
+    let document = roxmltree::Document::parse(report).map_err(|e| format!("invalid JUnit report: {}", e))?;
+
+    let cases = document
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .map(|node| {
+            let suite = node
+                .ancestors()
+                .find(|ancestor| ancestor.has_tag_name("testsuite"))
+                .and_then(|suite| suite.attribute("name"))
+                .or_else(|| node.attribute("classname"))
+                .map(String::from);
+
+            let outcome = node
+                .children()
+                .find(|child| child.has_tag_name("failure") || child.has_tag_name("error") || child.has_tag_name("skipped"));
+            let status = match outcome {
+                Some(child) if child.has_tag_name("skipped") => TestStatus::Skipped,
+                Some(_) => TestStatus::Failed,
+                None => TestStatus::Passed,
+            };
+            let message = outcome.and_then(|child| {
+                let parts: Vec<&str> = [child.attribute("message"), child.text()]
+                    .into_iter()
+                    .flatten()
+                    .map(str::trim)
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                (!parts.is_empty()).then(|| parts.join("\n"))
+            });
+
+            TestCase {
+                name: node.attribute("name").unwrap_or_default().to_string(),
+                suite,
+                status,
+                message,
+            }
+        })
+        .collect();
+    Ok(cases)
+}
+
+async fn run(root: &Path, runner: &TestRunner) -> (Vec<TestCase>, Option<String>) {
+    let cwd = match &runner.cwd {
+        Some(cwd) => root.join(cwd),
+        None => root.to_path_buf(),
+    };
+
+    let child = shell(&runner.command)
+        .current_dir(&cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(e) => return (Vec::new(), Some(format!("cannot start `{}` in {}: {}", runner.command, cwd.display(), e))),
+    };
+
+    let output = match tokio::time::timeout(Duration::from_millis(runner.timeout_ms), child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return (Vec::new(), Some(e.to_string())),
+        Err(_) => return (Vec::new(), Some(format!("timed out after {} ms", runner.timeout_ms))),
+    };
+
+    let report = match &runner.report {
+        Some(report) => match tokio::fs::read_to_string(root.join(report)).await {
+            Ok(content) => content,
+            Err(e) => return (Vec::new(), Some(format!("cannot read {}: {}", report, e))),
+        },
+        None => String::from_utf8_lossy(&output.stdout).into_owned(),
+    };
+
+    let (cases, parse_error) = match runner.format {
+        TestFormat::Tap => (parse_tap(&report), None),
+        TestFormat::Junit => match parse_junit(&report) {
+            Ok(cases) => (cases, None),
+            Err(e) => (Vec::new(), Some(e)),
+        },
+    };
+
+    let error = parse_error.or_else(|| {
+        if output.status.success() {
+            return None;
+        }
+        // Without parsed cases the runner's own output is all there is to show
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.trim() {
+            last if cases.is_empty() && !last.is_empty() => Some(format!("exited with {}: {}", output.status, last)),
+            _ => Some(format!("exited with {}", output.status)),
+        }
+    });
+    (cases, error)
+}
+
+/// Runs the tests of `resource` in the background, aborting the returned
+/// handle kills them.
+pub fn spawn_tests(state: Arc<WatcherState>, resource: String, root: PathBuf, runner: TestRunner) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("🧪 Testing {}: {}", resource, runner.command);
+        state.log("test", format!("{}: {}", resource, runner.command));
+
+        let started = Instant::now();
+        let (cases, error) = run(&root, &runner).await;
+        let count = |status: TestStatus| cases.iter().filter(|case| case.status == status).count();
+
+        let report = TestReport {
+            resource: resource.clone(),
+            success: error.is_none() && count(TestStatus::Failed) == 0,
+            duration_ms: started.elapsed().as_millis() as u64,
+            passed: count(TestStatus::Passed),
+            failed: count(TestStatus::Failed),
+            skipped: count(TestStatus::Skipped),
+            cases,
+            error,
+        };
+
+        if report.success {
+            info!("✅ Tests of {} passed ({} passed, {} skipped)", resource, report.passed, report.skipped);
+        } else {
+            warn!(
+                "❌ Tests of {} failed ({} failed, {} passed){}",
+                resource,
+                report.failed,
+                report.passed,
+                report.error.as_ref().map(|e| format!(": {}", e)).unwrap_or_default()
+            );
+        }
+        state.log(
+            "test",
+            format!("{}: {} passed, {} failed, {} skipped", resource, report.passed, report.failed, report.skipped),
+        );
+        state.broadcast(WatcherMessage::TestReport(report));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(cases: &[TestCase]) -> Vec<(&str, TestStatus)> {
+        cases.iter().map(|case| (case.name.as_str(), case.status)).collect()
+    }
+
+    #[test]
+    fn tap_directives_and_diagnostics() {
+        let output = "\
+TAP version 13
+ok 1 - loads the config
+not ok 2 - saves the player
+  ---
+  message: 'expected 3, got 4'
+  at: test/player.js:12:7
+  ...
+ok 3 - sync # SKIP no database
+not ok 4 - websocket # TODO not written yet
+okay, moving on
+1..4
+# tests 4";
+        let cases = parse_tap(output);
+        assert_eq!(
+            statuses(&cases),
+            [
+                ("loads the config", TestStatus::Passed),
+                ("saves the player", TestStatus::Failed),
+                ("sync", TestStatus::Skipped),
+                ("websocket", TestStatus::Skipped),
+            ]
+        );
+        assert_eq!(cases[1].message.as_deref(), Some("message: 'expected 3, got 4'\nat: test/player.js:12:7"));
+        assert_eq!(cases[2].message.as_deref(), Some("SKIP no database"));
+    }
+
+    #[test]
+    fn tap_plan_ends_the_last_failure() {
+        let cases = parse_tap("not ok 1 - breaks\n# boom\n1..1\n# fail 1");
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].message.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn tap_subtests_from_node_test() {
+        let output = "\
+TAP version 13
+# Subtest: inventory
+    # Subtest: adds items
+    ok 1 - adds items
+      ---
+      duration_ms: 0.4
+      ...
+    # Subtest: drops items
+    not ok 2 - drops items
+      ---
+      error: 'weight is negative'
+      ...
+    1..2
+not ok 1 - inventory
+  ---
+  error: '1 subtest failed'
+  ...
+# Subtest: standalone
+ok 2 - standalone
+1..2";
+        let cases = parse_tap(output);
+        assert_eq!(
+            statuses(&cases),
+            [("adds items", TestStatus::Passed), ("drops items", TestStatus::Failed), ("standalone", TestStatus::Passed)]
+        );
+        assert_eq!(cases[0].suite.as_deref(), Some("inventory"));
+        assert_eq!(cases[1].message.as_deref(), Some("error: 'weight is negative'"));
+        assert_eq!(cases[2].suite, None);
+    }
+
+    #[test]
+    fn junit_from_jest() {
+        let report = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="jest tests" tests="3" failures="1" errors="0" time="1.2">
+  <testsuite name="inventory" errors="0" failures="1" skipped="1" timestamp="2024-03-01T10:00:00" time="0.8" tests="3">
+    <testcase classname="inventory adds items" name="inventory adds items" time="0.002">
+    </testcase>
+    <testcase classname="inventory drops items" name="inventory drops items" time="0.003">
+      <failure>Error: expect(received).toBe(expected)
+
+Expected: 3
+Received: 4
+    at Object.&lt;anonymous&gt; (src/inventory.test.ts:12:20)</failure>
+    </testcase>
+    <testcase classname="inventory sorts" name="inventory sorts" time="0">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        let cases = parse_junit(report).unwrap();
+        assert_eq!(
+            statuses(&cases),
+            [
+                ("inventory adds items", TestStatus::Passed),
+                ("inventory drops items", TestStatus::Failed),
+                ("inventory sorts", TestStatus::Skipped),
+            ]
+        );
+        assert_eq!(cases[1].suite.as_deref(), Some("inventory"));
+        assert!(cases[1].message.as_deref().unwrap().starts_with("Error: expect(received).toBe(expected)"));
+        assert_eq!(cases[2].message, None);
+    }
+
+    #[test]
+    fn junit_from_busted() {
+        let report = r#"<testsuites>
+<testsuite name="Busted Suite" timestamp="2024-03-01T10:00:00" hostname="dev" time="0.01" tests="2" failures="1" errors="0" skip="0">
+<testcase classname="spec/money_spec.lua" name="money adds" time="0.001"></testcase>
+<testcase classname="spec/money_spec.lua" name="money rounds" time="0.002"><failure type="failure" message="spec/money_spec.lua:8: Expected objects to be the same.">stack traceback:
+	spec/money_spec.lua:8: in function &lt;spec/money_spec.lua:6&gt;
+</failure></testcase>
+</testsuite>
+</testsuites>"#;
+        let cases = parse_junit(report).unwrap();
+        assert_eq!(statuses(&cases), [("money adds", TestStatus::Passed), ("money rounds", TestStatus::Failed)]);
+        let message = cases[1].message.as_deref().unwrap();
+        assert!(message.starts_with("spec/money_spec.lua:8: Expected objects to be the same.\nstack traceback:"));
+    }
+
+    #[test]
+    fn junit_rejects_invalid_xml() {
+        assert!(parse_junit("<testsuite><testcase").is_err());
+    }
+}
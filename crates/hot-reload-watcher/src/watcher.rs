@@ -1,144 +1,238 @@
+use crate::backend::resolve_watch_mode;
 use crate::config::WatcherConfig;
-use tokio::sync::Mutex as TokioMutex;
-use tokio_tungstenite::{accept_async, WebSocketStream, MaybeTlsStream, tungstenite::Message};
+use crate::fx::{FxCommand, FxRequest};
+use crate::history::{self, HistoryQuery};
+use crate::pipeline;
+use crate::scanner::build_index;
+use crate::state::WatcherState;
+use tokio_tungstenite::{accept_async, WebSocketStream, tungstenite::Message};
 use tokio::signal;
-use tokio_tungstenite::connect_async;
 use tokio::net::{TcpListener, TcpStream};
-use tracing::{info, error};
-use walkdir::{WalkDir, Error as WalkDirError};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tracing::{info, error, warn};
 use futures::{SinkExt, StreamExt};
 use futures::stream::SplitSink;
-use notify::{Watcher, RecursiveMode, Event, EventKind};
-use std::path::Path;
-use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use std::sync::Arc;
 use std::error::Error;
-use hot_reload_common::{ResourceChange, ChangeType, InitialData, AuthRequest, AuthResponse};
-use tokio::sync::mpsc;
-use std::collections::HashSet;
+use xxhash_rust::xxh3::xxh3_64;
+use hot_reload_common::{InitialData, ActionState, ActionStatus, AuthRequest, AuthResponse, ClientMessage, CommandResult, HistoryEvent, ReloadStrategies, ResourceAction, ResourceStates, Role, Session, WatcherMessage};
 
 type BoxError = Box<dyn Error + Send + Sync>;
 
-async fn scan_resources(_: &str) -> Result<HashMap<String, Vec<String>>, BoxError> {
-    info!("📂 Start scanning resources");
-    let path = Path::new("./resources");
-    if !path.exists() {
-        error!("❌ Folder ./resources doesn't exist in current directory");
-        error!("❌ Ensure you run the executable from the root of the FiveM server");
-        return Err("Folder resources doesn't exist".into());
-    }
-
-    info!("📂 Scan folder: {}", path.display());
-    let valid_extensions: HashSet<&str> = ["lua", "js", "dll"].into_iter().collect();
-
-    // TODO : Manage ignored folders & files from UI and sync it with all clients
-    let ignored_folders: HashSet<&str> = [
-        "node_modules", ".git", "target",
-        ".idea", ".vscode", "vendor", "tmp", "temp",
-        "logs", "coverage", ".next", ".nuxt", ".cache"
-    ].into_iter().collect();
-    let ignored_files: HashSet<&str> = [
-        "package-lock.json", "yarn.lock", "pnpm-lock.yaml",
-        "README.md", "LICENSE", ".gitignore", ".env",
-        "tsconfig.json", "package.json", "webpack.config.js"
-    ].into_iter().collect();
-
-    let mut resource_list = Vec::new();
-    for entry in WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_str().unwrap_or_default();
-            !ignored_folders.contains(name) && !name.starts_with('.')
-        }) {
-        let entry = entry.map_err(|e: WalkDirError| -> BoxError { Box::new(e) })?;
-        
-        if entry.file_type().is_dir() {
-            let resource_path = entry.path();
-            
-            if resource_path.join("fxmanifest.lua").exists() || resource_path.join("__resource.lua").exists() {
-                let resource_name = resource_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or_default()
-                    .to_string();
-
-                //info!("🔍 Ressource trouvée: {}", resource_name);
-                let mut resource_files = Vec::new();
-                
-                for file in WalkDir::new(resource_path)
-                    .into_iter()
-                    .filter_entry(|e| {
-                        let name = e.file_name().to_str().unwrap_or_default();
-                        !ignored_folders.contains(name) && !name.starts_with('.')
-                    })
-                    .filter_map(|e| e.ok()) {
-                    if file.file_type().is_file() {
-                        let file_name = file.file_name().to_str().unwrap_or_default();
-                        if ignored_files.contains(file_name) {
-                            continue;
-                        }
+/// Answers a request from one client, the reply only goes back to that client.
+fn handle_client_message(
+    state: &WatcherState,
+    role: Role,
+    user: &str,
+    direct: &mpsc::UnboundedSender<WatcherMessage>,
+    text: &str,
+) -> Option<WatcherMessage> {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("⚠️ Unknown client message: {}", e);
+            return None;
+        }
+    };
 
-                        if let Some(ext) = file.path().extension() {
-                            if let Some(ext_str) = ext.to_str() {
-                                if valid_extensions.contains(ext_str) {
-                                    if let Ok(relative_path) = file.path().strip_prefix(resource_path) {
-                                        let file_path = relative_path.to_string_lossy().to_string();
-                                        info!("📄 Fichier trouvé dans {}: {}", resource_name, file_path);
-                                        resource_files.push(file_path);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    if let Some(resources) = request_resources(state, &message) {
+        let refused = role < Role::Operator;
+        // Console commands that run are recorded with their outcome by the pipeline
+        if refused || !matches!(message, ClientMessage::ExecuteCommand { .. }) {
+            state.record(Some(user), resources, HistoryEvent::Request { request: message.clone(), refused });
+        }
+    }
 
-                if !resource_files.is_empty() {
-                    resource_files.sort_by_cached_key(|a| a.to_lowercase());
-                    resource_list.push((resource_name, resource_files));
+    match message {
+        // Names the connection, handled where its user is kept
+        ClientMessage::Hello { .. } => None,
+        ClientMessage::GetPipelineLog => {
+            let log = state.pipeline_log.lock().ok()?.snapshot();
+            Some(WatcherMessage::PipelineLog(log))
+        }
+        // Custom strategies run their commands in the FXServer console, like `ExecuteCommand`
+        ClientMessage::SetReloadStrategy { resource, .. } if role < Role::Operator => {
+            warn!("⚠️ Reload strategy change of {} refused, the client is not an operator", resource);
+            let strategies = state.strategies.lock().ok()?.clone();
+            Some(WatcherMessage::ReloadStrategies(ReloadStrategies { strategies, error: None }))
+        }
+        ClientMessage::SetReloadStrategy { resource, strategy } => {
+            info!("🛠️ Reload strategy of {} set to {:?}", resource, strategy);
+            let Err(e) = state.set_strategy(resource, strategy) else {
+                return None;
+            };
+            error!("❌ Could not save config.hotwatch.json: {}", e);
+            let strategies = state.strategies.lock().ok()?.clone();
+            Some(WatcherMessage::ReloadStrategies(ReloadStrategies { strategies, error: Some(e) }))
+        }
+        ClientMessage::ExecuteCommand { id, command } => {
+            if role < Role::Operator {
+                warn!("⚠️ Console command refused, the client is not an operator: {}", command);
+                return Some(WatcherMessage::CommandResult(CommandResult {
+                    id,
+                    command,
+                    output: Vec::new(),
+                    error: Some("operator role required".to_string()),
+                }));
+            }
+            let _ = state.requests.send(FxRequest::Command(FxCommand {
+                id,
+                command,
+                reply: direct.clone(),
+                user: user.to_string(),
+            }));
+            None
+        }
+        ClientMessage::ResourceAction { resources, action } => {
+            if role < Role::Operator {
+                warn!("⚠️ Resource action refused, the client is not an operator: {}", action.name());
+                for resource in resources {
+                    let _ = direct.send(WatcherMessage::ActionStatus(ActionStatus {
+                        resource,
+                        action,
+                        state: ActionState::Failed,
+                        message: Some("operator role required".to_string()),
+                    }));
                 }
+                return None;
+            }
+            info!("🖱️ {} requested for {}", action.name(), resources.join(", "));
+            let _ = state.requests.send(FxRequest::Action { resources, action, user: user.to_string() });
+            None
+        }
+        ClientMessage::SetApplyMode { .. } | ClientMessage::ApplyPending { .. } if role < Role::Operator => {
+            warn!("⚠️ Apply request refused, the client is not an operator");
+            Some(WatcherMessage::PendingChanges(state.pending_changes()))
+        }
+        ClientMessage::SetApplyMode { mode } => {
+            state.set_apply_mode(mode, user);
+            None
+        }
+        ClientMessage::ApplyPending { resources } => {
+            let _ = state.requests.send(FxRequest::Apply { resources, user: user.to_string() });
+            None
+        }
+        ClientMessage::ResetBreaker { resource } if role < Role::Operator => {
+            warn!("⚠️ Breaker reset of {} refused, the client is not an operator", resource);
+            state.breaker_state(&resource).map(WatcherMessage::Breaker)
+        }
+        ClientMessage::ResetBreaker { resource } => {
+            state.reset_breaker(&resource, true);
+            // Nothing is broadcast for a resource that never failed, the client still gets its state
+            state.breaker_state(&resource).map(WatcherMessage::Breaker)
+        }
+        ClientMessage::Rollback { resource, .. } if role < Role::Operator => {
+            warn!("⚠️ Rollback of {} refused, the client is not an operator", resource);
+            Some(WatcherMessage::ActionStatus(ActionStatus {
+                resource,
+                action: ResourceAction::Rollback,
+                state: ActionState::Failed,
+                message: Some("operator role required".to_string()),
+            }))
+        }
+        ClientMessage::Rollback { resource, snapshot } => {
+            info!("⏪ Rollback of {} requested", resource);
+            let _ = state.requests.send(FxRequest::Rollback { resource, snapshot, user: Some(user.to_string()) });
+            None
+        }
+        ClientMessage::Pause { .. } | ClientMessage::Resume { .. } if role < Role::Operator => {
+            warn!("⚠️ Pause request refused, the client is not an operator");
+            Some(WatcherMessage::Pause(state.pause_state()))
+        }
+        ClientMessage::Pause { reason, duration_ms } => {
+            state.pause(reason, duration_ms);
+            None
+        }
+        ClientMessage::Resume { apply } => {
+            let changes = state.resume(apply);
+            if !changes.is_empty() {
+                let _ = state.requests.send(FxRequest::Resume(changes));
             }
+            None
+        }
+        ClientMessage::QueryHistory { from_ms, to_ms, resource, user, limit } => {
+            let query = HistoryQuery { from_ms, to_ms, resource, user, limit };
+            let config = state.config.history.clone();
+            let direct = direct.clone();
+            // Rotated files can take a while to read, the connection keeps going meanwhile
+            tokio::task::spawn_blocking(move || {
+                let _ = direct.send(WatcherMessage::History(history::query(&config, &query)));
+            });
+            None
         }
     }
+}
 
-    resource_list.sort_by_cached_key(|(name, _)| name.to_lowercase());
-    let resources: HashMap<String, Vec<String>> = resource_list.into_iter().collect();
-
-    info!("🏁 Scan finished, {} resources found", resources.len());
-    if resources.is_empty() {
-        info!("⚠️ No resource with fxmanifest.lua found");
+/// Resources a request is about, `None` for the ones the history skips: reads
+/// and the apply mode every UI sends at connect when it does not change.
+fn request_resources(state: &WatcherState, message: &ClientMessage) -> Option<Vec<String>> {
+    match message {
+        ClientMessage::Hello { .. } | ClientMessage::GetPipelineLog | ClientMessage::QueryHistory { .. } => None,
+        ClientMessage::SetApplyMode { mode } if *mode == state.apply_mode() => None,
+        ClientMessage::SetReloadStrategy { resource, .. } | ClientMessage::ResetBreaker { resource } | ClientMessage::Rollback { resource, .. } => {
+            Some(vec![resource.clone()])
+        }
+        ClientMessage::ResourceAction { resources, .. } | ClientMessage::ApplyPending { resources } => Some(resources.clone()),
+        ClientMessage::ExecuteCommand { .. } | ClientMessage::Pause { .. } | ClientMessage::Resume { .. } | ClientMessage::SetApplyMode { .. } => {
+            Some(Vec::new())
+        }
     }
+}
 
-    Ok(resources)
+/// `alice (local)`, the name a client gave kept short and on one line.
+fn named(name: &str, client: &str) -> String {
+    let name: String = name.chars().filter(|c| !c.is_control()).take(40).collect();
+    match name.trim() {
+        "" => client.to_string(),
+        name => format!("{} ({})", name, client),
+    }
 }
 
-async fn handle_connection(stream: TcpStream, config: &Arc<WatcherConfig>) -> Result<(), BoxError> {
+async fn handle_connection(stream: TcpStream, state: Arc<WatcherState>) -> Result<(), BoxError> {
     let addr = stream.peer_addr()?;
     let is_localhost = addr.ip().is_loopback();
     let ws_stream = accept_async(stream).await?;
     let (mut ws_write, mut ws_read) = ws_stream.split();
-    let (tx, mut rx) = mpsc::channel::<Message>(1000);
-    let tx = Arc::new(tx);
+    let config = &state.config;
 
+    let mut role = if is_localhost { Role::Operator } else { Role::Viewer };
+    // Named in the history, api keys only by a fingerprint
+    let mut user = if is_localhost { "local".to_string() } else { addr.ip().to_string() };
+    // Remote clients have to start with a valid api key, anything else closes the connection
     if !is_localhost {
-        if let Some(Ok(msg)) = ws_read.next().await {
-            if let Ok(auth) = serde_json::from_str::<AuthRequest>(&msg.to_string()) {
-                if auth.api_key != config.api_key {
-                    let response = AuthResponse::Failed("Clé API invalide".to_string());
-                    tx.send(Message::Text(serde_json::to_string(&response)?)).await?;
-                    return Ok(());
-                }
-                let response = AuthResponse::Success;
-                tx.send(Message::Text(serde_json::to_string(&response)?)).await?;
-            }
-        }
+        let Some(Ok(msg)) = ws_read.next().await else {
+            return Ok(());
+        };
+        let auth = serde_json::from_str::<AuthRequest>(&msg.to_string()).ok();
+        let granted = auth.as_ref().and_then(|auth| config.role_of(&auth.api_key));
+        let (Some(auth), Some(granted)) = (auth, granted) else {
+            warn!("⚠️ Connection from {} refused, no valid api key", addr);
+            let response = AuthResponse::Failed("Clé API invalide".to_string());
+            ws_write.send(Message::Text(serde_json::to_string(&response)?)).await?;
+            let _ = ws_write.close().await;
+            return Ok(());
+        };
+        role = granted;
+        user = format!("key-{:08x}@{}", xxh3_64(auth.api_key.as_bytes()) as u32, addr.ip());
+        let response = AuthResponse::Success;
+        ws_write.send(Message::Text(serde_json::to_string(&response)?)).await?;
     }
+    let client = user.clone();
+
+    // Subscribe before reading the index so nothing between the two gets lost
+    let mut events_rx = state.events.subscribe();
+    let mut fivem_rx = state.fivem.subscribe();
+    let mut console_rx = state.console.subscribe();
+
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel();
 
     info!("📤 Sending initial data to client");
-    let resources = scan_resources(&config.resources_path).await?;
+    let resources = state.index.lock().map_err(|e| e.to_string())?.tree();
     let initial_data = InitialData {
         resources_path: config.resources_path.clone(),
-        resources: resources.clone(),
+        resources,
     };
 
     let initial_data_str = serde_json::to_string(&initial_data)?;
@@ -146,160 +240,151 @@ async fn handle_connection(stream: TcpStream, config: &Arc<WatcherConfig>) -> Re
     ws_write.send(Message::Text(initial_data_str)).await?;
     info!("✅ Initial data sent");
 
-    let fx_stream = connect_to_fxserver(config).await?;
-    let fx_stream = Arc::new(TokioMutex::new(fx_stream));
-    let fx_stream_clone = fx_stream.clone();
-    let runtime = tokio::runtime::Handle::current();
-    let last_events = Arc::new(TokioMutex::new(HashMap::new()));
-    let last_events_clone = last_events.clone();
-    
-    let mut watcher = {
-        let tx = tx.clone();
-        notify::recommended_watcher(move |res: Result<Event, _>| {
-            let tx = tx.clone();
-            let rt = runtime.clone();
-            let last_events = last_events_clone.clone();
-            let fx_stream = fx_stream_clone.clone();
-
-            if let Ok(event) = res {
-                let should_process = event.paths.iter().any(|path| {
-                    path.extension()
-                        .and_then(|ext| ext.to_str())
-                        .map(|ext| matches!(ext, "lua" | "js" | "dll"))
-                        .unwrap_or(false)
-                });
+    ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Session(Session { role, user: user.clone() }))?)).await?;
 
-                if !should_process {
-                    return ();
-                }
+    let report = state.scan_report.lock().map_err(|e| e.to_string())?.clone();
+    ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::ScanReport(report))?)).await?;
+    info!("✅ Scan report sent");
 
-                if let Some(path) = event.paths.first() {
-                    let path = path.to_path_buf();
-                    let path_str = path.to_string_lossy().into_owned();
-                    let event_kind = event.kind;
+    if let Some(graph) = state.graph_snapshot() {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Graph(graph))?)).await?;
+    }
 
-                    let _ = rt.spawn(async move {
-                        let mut last_events = last_events.lock().await;
-                        let now = Instant::now();
+    let strategies = state.strategies.lock().map_err(|e| e.to_string())?.clone();
+    let message = WatcherMessage::ReloadStrategies(ReloadStrategies { strategies, error: None });
+    ws_write.send(Message::Text(serde_json::to_string(&message)?)).await?;
 
-                        if let Some(last_time) = last_events.get(&path_str) {
-                            if now.duration_since(*last_time) < Duration::from_secs(1) {
-                                return Ok::<(), BoxError>(());
-                            }
-                        }
+    for snapshots in state.snapshots.all() {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Snapshots(snapshots))?)).await?;
+    }
+    ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Pause(state.pause_state()))?)).await?;
+    ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::PendingChanges(state.pending_changes()))?)).await?;
 
-                        last_events.insert(path_str.clone(), now);
-                        drop(last_events);
-
-                        if let Some(resource_name) = path.parent()
-                            .and_then(|p| p.file_name())
-                            .and_then(|n| n.to_str())
-                            .map(String::from) {
-                            
-                            let change = ResourceChange {
-                                resource_name: resource_name.clone(),
-                                change_type: match event_kind {
-                                    EventKind::Create(_) => ChangeType::FileAdded,
-                                    EventKind::Modify(_) => ChangeType::FileModified,
-                                    EventKind::Remove(_) => ChangeType::FileRemoved,
-                                    _ => return Ok(()),
-                                },
-                                file_path: path_str,
-                            };
-
-                            info!("✨ Change detected: {:?}", change);
-                            let fx_response = {
-                                let mut fx = fx_stream.lock().await;
-                                if let Ok(message) = serde_json::to_string(&change) {
-                                    if let Ok(_) = fx.send(Message::Text(message)).await {
-                                        info!("✅ Message sent to FXserver");
-                                        
-                                        if let Some(Ok(response)) = fx.next().await {
-                                            if let Ok(text) = response.to_text() {
-                                                Some(text.to_string())
-                                            } else {
-                                                None
-                                            }
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            };
-
-                            if let Some(response_text) = fx_response {
-                                info!("FXserver response: {}", response_text);
-                                
-                                let message = serde_json::json!({
-                                    "type": "fivem_response",
-                                    "message": response_text
-                                });
-
-                                if let Ok(message_str) = serde_json::to_string(&message) {
-                                    info!("🔄 Sending via handler...");
-                                    
-                                    if let Err(e) = tx.send(Message::Text(message_str)).await {
-                                        error!("❌ Error sending to handler: {}", e);
-                                    } else {
-                                        info!("✅ Message sent to handler");
-                                    }
-                                }
-                            }
+    let states = state.resource_states.lock().map_err(|e| e.to_string())?.clone();
+    if !states.is_empty() {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::ResourceStates(ResourceStates { states }))?)).await?;
+    }
 
-                            Ok::<(), BoxError>(())
-                        } else {
-                            Ok::<(), BoxError>(())
-                        }
-                    });
-                }
-            }()
-        })?
-    };
+    let lint_reports: Vec<_> = state.lint_reports.lock().map_err(|e| e.to_string())?.values().cloned().collect();
+    for report in lint_reports {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Lint(report))?)).await?;
+    }
+
+    let script_errors: Vec<_> = state.script_errors.lock().map_err(|e| e.to_string())?.iter().cloned().collect();
+    for error in script_errors {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::ScriptError(error))?)).await?;
+    }
+
+    for breaker in state.breaker_states() {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Breaker(breaker))?)).await?;
+    }
+
+    let console_history: Vec<_> = state.console_history.lock().map_err(|e| e.to_string())?.iter().cloned().collect();
+    for line in console_history {
+        ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Console(line))?)).await?;
+    }
+
+    let mut last_stats = state.stats.snapshot();
+    ws_write.send(Message::Text(serde_json::to_string(&WatcherMessage::Stats(last_stats.clone()))?)).await?;
 
-    watcher.watch(Path::new(&config.resources_path), RecursiveMode::Recursive)?;
-    info!("✅ Monitoring resources enabled");
     let mut pending_messages = Vec::with_capacity(100);
     let mut last_batch_time = std::time::Instant::now();
 
     loop {
         tokio::select! {
-            Some(ws_msg) = ws_read.next() => {
+            ws_msg = ws_read.next() => {
                 match ws_msg {
-                    Ok(msg) => {
+                    Some(Ok(msg)) => {
                         if let Ok(text) = msg.to_text() {
                             info!("📨 Message received from client: {}", text);
+                            if let Ok(ClientMessage::Hello { user: name }) = serde_json::from_str(text) {
+                                user = named(&name, &client);
+                                info!("👤 {} is {}", client, user);
+                                let session = WatcherMessage::Session(Session { role, user: user.clone() });
+                                ws_write.send(Message::Text(serde_json::to_string(&session)?)).await?;
+                            } else if let Some(reply) = handle_client_message(&state, role, &user, &direct_tx, text) {
+                                ws_write.send(Message::Text(serde_json::to_string(&reply)?)).await?;
+                            }
                         }
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         error!("❌ WebSocket error: {}", e);
                         break;
                     }
+                    None => break,
                 }
             }
-            
-            Some(message) = rx.recv() => {
-                if let Ok(text) = message.to_text() {
-                    info!("📨 Message received for batch: {}", text);
-                    pending_messages.push(text.to_string());
-                    
-                    if pending_messages.len() >= 10 || last_batch_time.elapsed() > Duration::from_millis(100) {
-                        if !pending_messages.is_empty() {
-                            info!("🔄 Processing batch of {} messages", pending_messages.len());
-                            if let Err(e) = process_message_batch(&pending_messages, &mut ws_write).await {
-                                error!("❌ Error sending batch: {}", e);
-                            }
-                            pending_messages.clear();
-                            last_batch_time = Instant::now();
+
+            message = events_rx.recv() => {
+                match message {
+                    Ok(message) => {
+                        let message = serde_json::to_string(&message)?;
+                        if let Err(e) = ws_write.send(Message::Text(message)).await {
+                            error!("❌ Error sending watcher message: {}", e);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => warn!("⚠️ Client too slow, {} message(s) skipped", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+
+            Some(message) = direct_rx.recv() => {
+                let message = serde_json::to_string(&message)?;
+                if let Err(e) = ws_write.send(Message::Text(message)).await {
+                    error!("❌ Error sending watcher message: {}", e);
+                }
+            }
+
+            line = console_rx.recv() => {
+                match line {
+                    Ok(line) => {
+                        let message = serde_json::to_string(&WatcherMessage::Console(line))?;
+                        if let Err(e) = ws_write.send(Message::Text(message)).await {
+                            error!("❌ Error sending console line: {}", e);
                         }
                     }
+                    Err(RecvError::Lagged(skipped)) => warn!("⚠️ Client too slow, {} console line(s) skipped", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+
+            response = fivem_rx.recv() => {
+                let text = match response {
+                    Ok(text) => text,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("⚠️ Client too slow, {} FXserver response(s) skipped", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let message = serde_json::json!({
+                    "type": "fivem_response",
+                    "message": text
+                });
+                let text = serde_json::to_string(&message)?;
+                info!("📨 Message received for batch: {}", text);
+                pending_messages.push(text);
+
+                if pending_messages.len() >= 10 || last_batch_time.elapsed() > Duration::from_millis(100) {
+                    info!("🔄 Processing batch of {} messages", pending_messages.len());
+                    if let Err(e) = process_message_batch(&pending_messages, &mut ws_write).await {
+                        error!("❌ Error sending batch: {}", e);
+                    }
+                    pending_messages.clear();
+                    last_batch_time = Instant::now();
                 }
             }
 
             _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                let current_stats = state.stats.snapshot();
+                if current_stats != last_stats {
+                    let message = serde_json::to_string(&WatcherMessage::Stats(current_stats.clone()))?;
+                    if let Err(e) = ws_write.send(Message::Text(message)).await {
+                        error!("❌ Error sending stats: {}", e);
+                    }
+                    last_stats = current_stats;
+                }
+
                 if !pending_messages.is_empty() {
                     info!("🔄 Processing batch by timeout");
                     if let Err(e) = process_message_batch(&pending_messages, &mut ws_write).await {
@@ -315,15 +400,6 @@ async fn handle_connection(stream: TcpStream, config: &Arc<WatcherConfig>) -> Re
     Ok(())
 }
 
-async fn connect_to_fxserver(config: &WatcherConfig) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, BoxError> {
-    let fivem_url = format!("ws://localhost:{}", config.fxserver_port);
-    info!("🔌 Trying to connect to FXserver on {}", fivem_url);
-    let (ws_stream, _) = connect_async(&fivem_url).await?;
-    info!("✅ FXserver connection established!");
-    
-    Ok(ws_stream)
-}
-
 async fn process_message_batch(
     messages: &[String],
     ws_write: &mut SplitSink<WebSocketStream<TcpStream>, Message>
@@ -346,15 +422,23 @@ async fn process_message_batch(
 }
 
 
-pub async fn run(config: WatcherConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let addr = format!("{}:{}", config.ws_host, config.ws_port);
+pub async fn run(mut config: WatcherConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
+    config.watch_mode = resolve_watch_mode(&config).await;
+
+    // Walking and hashing thousands of files is blocking work, keep it off the runtime workers
+    let root = config.watch_root();
+    let scan = tokio::task::spawn_blocking(move || build_index(&root)).await??;
+    let (requests_tx, requests_rx) = mpsc::unbounded_channel();
+    let state = Arc::new(WatcherState::new(config, scan, requests_tx));
+    let _watcher = pipeline::start(state.clone(), requests_rx)?;
+
+    let addr = format!("{}:{}", state.config.ws_host, state.config.ws_port);
     let listener = TcpListener::bind(&addr).await?;
     info!("🚀 WebSocket server started on {}", addr);
     info!("👀 Waiting for connections...");
 
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::broadcast::channel(1);
     let shutdown_tx = Arc::new(shutdown_tx);
-    let config = Arc::new(config);
 
     let shutdown_tx_clone = shutdown_tx.clone();
     tokio::spawn(async move {
@@ -374,11 +458,10 @@ pub async fn run(config: WatcherConfig) -> Result<(), Box<dyn Error + Send + Syn
                 match accept_result {
                     Ok((stream, addr)) => {
                         info!("📡 New connection from: {}", addr);
-                        let _ = shutdown_tx.clone();
-                        let config = config.clone();
-                        
+                        let state = state.clone();
+
                         tokio::spawn(async move {
-                            match handle_connection(stream, &config).await {
+                            match handle_connection(stream, state).await {
                                 Ok(_) => info!("✅ Connection closed: {}", addr),
                                 Err(e) => error!("❌ Connection error: {}", e),
                             }
@@ -394,4 +477,4 @@ pub async fn run(config: WatcherConfig) -> Result<(), Box<dyn Error + Send + Syn
 
     info!("👋 Server shutdown");
     Ok(())
-}
\ No newline at end of file
+}
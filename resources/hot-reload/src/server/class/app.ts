@@ -1,13 +1,35 @@
 import { WebSocketServer, WebSocket } from 'ws';
 
+type ReloadStrategy =
+  | { kind: 'auto' }
+  | { kind: 'ensure' }
+  | { kind: 'restart' }
+  | { kind: 'stop_start' }
+  | { kind: 'refresh_ensure' }
+  | { kind: 'never' }
+  | { kind: 'custom'; commands: string[] };
+
 interface ResourceChange {
   resource_name: string;
   change_type: 'FileModified' | 'FileAdded' | 'FileRemoved' | 'ManifestChanged';
+  // Resolved by the watcher, older watchers don't send it
+  strategy?: ReloadStrategy;
+}
+
+interface ConsoleCommand {
+  type: 'command';
+  command: string;
+}
+
+interface StatesQuery {
+  type: 'states';
 }
 
 export class HotReloadServer {
   private wss: WebSocketServer;
   private clients: Set<WebSocket> = new Set();
+  // Console lines printed while a command from the watcher runs
+  private capture: string[] | null = null;
   // @ts-ignore
   private locale: string;
 
@@ -17,6 +39,8 @@ export class HotReloadServer {
     this.locale = GetConvar('locale', 'en-US');
 
     this.setupWebSocket();
+    this.setupConsoleStream();
+    this.setupStateEvents();
     console.log(`^2Hot Reload WebSocket started on port ${port}^0`);
   }
   
@@ -30,11 +54,23 @@ export class HotReloadServer {
       this.clients.add(ws);
 
       ws.on('message', async (data: any): Promise<void> => {
+        let change: ResourceChange | null = null;
         try {
-          const change: ResourceChange = JSON.parse(data.toString());
-          await this.handleResourceChange(change);
+          const message: ResourceChange | ConsoleCommand | StatesQuery = JSON.parse(data.toString());
+          if ('type' in message && message.type === 'command') {
+            await this.handleCommand(message.command);
+          } else if ('type' in message && message.type === 'states') {
+            this.sendResourceStates();
+          } else {
+            change = message as ResourceChange;
+            await this.handleResourceChange(change);
+          }
         } catch (error) {
           console.error('^1Error processing message:', error, '^0');
+          // The watcher waits for an answer to every change, fail it now instead of letting it time out
+          if (change !== null) {
+            this.sendMessageToWatcher(`error ${change.resource_name}: ${error}`);
+          }
         }
       });
 
@@ -45,6 +81,41 @@ export class HotReloadServer {
     });
   }
 
+  // Relays everything printed in the server console, the watcher tells
+  // these apart from command answers by their `type`
+  private setupConsoleStream(): void {
+    RegisterConsoleListener((channel: string, message: string): void => {
+      if (this.capture !== null) {
+        this.capture.push(...message.split('\n').filter((line: string): boolean => line.trim() !== ''));
+      }
+      if (this.clients.size === 0) return;
+      this.sendMessageToWatcher(JSON.stringify({ type: 'console', channel, message }));
+    });
+  }
+
+  // Pushes state changes as they happen, the watcher also asks for the
+  // whole table when it connects and after each reload
+  private setupStateEvents(): void {
+    const push = (resource: string, state: string): void => {
+      this.sendMessageToWatcher(JSON.stringify({ type: 'resource_state', resource, state }));
+    };
+    on('onResourceStarting', (resource: string): void => push(resource, 'starting'));
+    on('onResourceStart', (resource: string): void => push(resource, 'started'));
+    on('onResourceStop', (resource: string): void => push(resource, 'stopped'));
+    on('onResourceListRefresh', (): void => this.sendResourceStates());
+  }
+
+  private sendResourceStates(): void {
+    const states: Record<string, string> = {};
+    for (let i = 0; i < GetNumResources(); i++) {
+      const resource = GetResourceByFindIndex(i);
+      if (resource) {
+        states[resource] = GetResourceState(resource);
+      }
+    }
+    this.sendMessageToWatcher(JSON.stringify({ type: 'resource_states', states }));
+  }
+
   private sendMessageToWatcher(message: string): void {
     // const date = new Date().toLocaleString(this.locale, { hour12: false });
 
@@ -63,28 +134,69 @@ export class HotReloadServer {
 
     if (GetResourceState(resource_name) === 'missing') {
       console.log(`^1Ressource ${resource_name} introuvable^0`);
+      // The watcher waits for an answer, tell it instead of letting it time out
+      this.sendMessageToWatcher(`missing ${resource_name}`);
+      return;
+    }
+
+    const commands = this.commandsFor(change);
+    if (commands.length === 0) {
+      this.sendMessageToWatcher(`no command for ${resource_name}`);
       return;
     }
 
     try {
-      switch (change_type) {
-        case 'ManifestChanged':
-        case 'FileAdded':
-        case 'FileRemoved':
-          ExecuteCommand('refresh');
-          this.sendMessageToWatcher(`refresh`);
+      for (const command of commands) {
+        ExecuteCommand(command);
+        // Let the server pick up the new file list before starting the resource
+        if (command === 'refresh') {
           await this.wait(500);
-          ExecuteCommand(`ensure ${resource_name}`);
-          this.sendMessageToWatcher(`ensure ${resource_name}`);
-          break;
-
-        case 'FileModified':
-          ExecuteCommand(`ensure ${resource_name}`);
-          this.sendMessageToWatcher(`ensure ${resource_name}`);
-          break;
+        }
       }
+      // The watcher waits for a single answer per change
+      this.sendMessageToWatcher(commands.join('; '));
     } catch (error) {
       console.error(`^1Error restarting ${resource_name}:`, error, '^0');
+      this.sendMessageToWatcher(`error ${resource_name}: ${error}`);
+    }
+  }
+
+  private async handleCommand(command: string): Promise<void> {
+    this.capture = [];
+    let error: string | null = null;
+    try {
+      ExecuteCommand(command);
+      // Commands print asynchronously, give them a moment before answering
+      await this.wait(200);
+    } catch (e) {
+      error = String(e);
+    }
+    const output = this.capture;
+    this.capture = null;
+    this.sendMessageToWatcher(JSON.stringify({ output, error }));
+  }
+
+  private commandsFor(change: ResourceChange): string[] {
+    const { resource_name, change_type } = change;
+    const strategy: ReloadStrategy = change.strategy ?? { kind: 'auto' };
+
+    switch (strategy.kind) {
+      case 'auto':
+        return change_type === 'FileModified'
+          ? [`ensure ${resource_name}`]
+          : ['refresh', `ensure ${resource_name}`];
+      case 'ensure':
+        return [`ensure ${resource_name}`];
+      case 'restart':
+        return [`restart ${resource_name}`];
+      case 'stop_start':
+        return [`stop ${resource_name}`, `start ${resource_name}`];
+      case 'refresh_ensure':
+        return ['refresh', `ensure ${resource_name}`];
+      case 'never':
+        return [];
+      case 'custom':
+        return strategy.commands.map((command: string): string => command.split('{resource}').join(resource_name));
     }
   }
 